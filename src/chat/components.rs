@@ -0,0 +1,10 @@
+use bevy::prelude::*;
+
+#[derive(Component)]
+pub struct ChatUI;
+
+#[derive(Component)]
+pub struct ChatLogText;
+
+#[derive(Component)]
+pub struct ChatInputText;
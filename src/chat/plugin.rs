@@ -0,0 +1,40 @@
+use bevy::prelude::*;
+use bevy_matchbox::prelude::MultipleChannels;
+use bevy_matchbox::MatchboxSocket;
+
+use super::resources::{ChatInput, ChatLog, ChatRateLimiter};
+use super::systems::{chat_input, close_chat, receive_chat, setup_ui, update_chat_ui};
+use crate::AppState;
+
+pub struct ChatPlugin;
+
+/// This plugin handles text chat between peers, available in the lobby and during online rounds.
+/// Chat uses its own reliable socket channel so it never touches the GGRS input stream.
+impl Plugin for ChatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChatInput>()
+            .init_resource::<ChatLog>()
+            .init_resource::<ChatRateLimiter>()
+            .add_systems(OnExit(AppState::Loading), setup_ui)
+            .add_systems(OnEnter(AppState::MenuConnect), reset_chat)
+            .add_systems(OnExit(AppState::MenuConnect), close_chat)
+            .add_systems(OnExit(AppState::RoundOnline), close_chat)
+            .add_systems(
+                Update,
+                (chat_input, receive_chat)
+                    .chain()
+                    .run_if(
+                        in_state(AppState::MenuConnect).or_else(in_state(AppState::RoundOnline)),
+                    )
+                    .run_if(resource_exists::<MatchboxSocket<MultipleChannels>>()),
+            )
+            .add_systems(Update, update_chat_ui);
+    }
+}
+
+fn reset_chat(mut commands: Commands) {
+    // every lobby gets a fresh socket, old messages belong to the previous one
+    commands.insert_resource(ChatInput::default());
+    commands.insert_resource(ChatLog::default());
+    commands.insert_resource(ChatRateLimiter::default());
+}
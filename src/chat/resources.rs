@@ -0,0 +1,113 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_matchbox::matchbox_socket::Packet;
+use bevy_matchbox::prelude::PeerId;
+use serde::{Deserialize, Serialize};
+
+pub const CHAT_MESSAGE_MAX_LEN: usize = 80;
+pub const CHAT_LOG_MAX_LINES: usize = 8;
+/// How long the chat overlay stays up after the last message while closed
+pub const CHAT_FADE_SECS: f64 = 8.0;
+/// At most CHAT_RATE_LIMIT messages per CHAT_RATE_WINDOW seconds, per sender
+pub const CHAT_RATE_LIMIT: usize = 3;
+pub const CHAT_RATE_WINDOW: f64 = 5.0;
+
+/// Chat message sent over the reliable chat channel, the sender is known from the peer id
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ChatMessage {
+    pub text: String,
+}
+
+impl ChatMessage {
+    pub fn to_packet(&self) -> Option<Packet> {
+        match ron::to_string(self) {
+            Ok(data) => Some(data.into_bytes().into_boxed_slice()),
+            Err(e) => {
+                warn!("unable to serialize chat message: {:?}", e);
+                None
+            }
+        }
+    }
+
+    pub fn from_packet(packet: &Packet) -> Option<ChatMessage> {
+        let data = std::str::from_utf8(packet).ok()?;
+        ron::from_str(data).ok()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ChatLine {
+    pub from: Option<String>,
+    pub text: String,
+}
+
+impl ChatLine {
+    pub fn format(&self) -> String {
+        match &self.from {
+            Some(from) => format!("{}: {}", from, self.text),
+            None => format!("* {}", self.text),
+        }
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct ChatLog {
+    pub lines: VecDeque<ChatLine>,
+    pub last_message: f64,
+}
+
+impl ChatLog {
+    pub fn push(&mut self, line: ChatLine, now: f64) {
+        self.lines.push_back(line);
+        while self.lines.len() > CHAT_LOG_MAX_LINES {
+            self.lines.pop_front();
+        }
+        self.last_message = now;
+    }
+
+    /// Messages generated locally, like rate limit warnings
+    pub fn notice(&mut self, text: &str, now: f64) {
+        let line = ChatLine {
+            from: None,
+            text: text.to_owned(),
+        };
+        self.push(line, now);
+    }
+}
+
+/// Local chat prompt, gameplay input is ignored while it is open
+#[derive(Default, Resource)]
+pub struct ChatInput {
+    pub open: bool,
+    pub buffer: String,
+}
+
+/// Sliding window of recent message timestamps for the local player and every peer
+#[derive(Default, Resource)]
+pub struct ChatRateLimiter {
+    local: VecDeque<f64>,
+    peers: HashMap<PeerId, VecDeque<f64>>,
+}
+
+impl ChatRateLimiter {
+    fn allow(sent: &mut VecDeque<f64>, now: f64) -> bool {
+        while sent.front().is_some_and(|t| now - t > CHAT_RATE_WINDOW) {
+            sent.pop_front();
+        }
+        if sent.len() >= CHAT_RATE_LIMIT {
+            return false;
+        }
+        sent.push_back(now);
+        true
+    }
+
+    pub fn allow_local(&mut self, now: f64) -> bool {
+        Self::allow(&mut self.local, now)
+    }
+
+    pub fn allow_peer(&mut self, peer: PeerId, now: f64) -> bool {
+        Self::allow(self.peers.entry(peer).or_default(), now)
+    }
+}
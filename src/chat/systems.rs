@@ -0,0 +1,233 @@
+use super::components::{ChatInputText, ChatLogText, ChatUI};
+use super::resources::{
+    ChatInput, ChatLine, ChatLog, ChatMessage, ChatRateLimiter, CHAT_FADE_SECS,
+    CHAT_MESSAGE_MAX_LEN,
+};
+use crate::loading::FontAssets;
+use crate::menu::lobby::{local_name, LobbyRoom, CHAT_CHANNEL};
+use crate::stats::profile::PlayerProfile;
+use crate::AppState;
+use bevy::prelude::*;
+use bevy_matchbox::prelude::MultipleChannels;
+use bevy_matchbox::MatchboxSocket;
+
+const CHAT_OPEN_KEY: KeyCode = KeyCode::T;
+
+pub fn setup_ui(mut commands: Commands, font_assets: Res<FontAssets>) {
+    // root node
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Auto,
+                right: Val::Px(10.),
+                top: Val::Auto,
+                bottom: Val::Px(10.),
+                width: Val::Px(420.),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(6.)),
+                ..Default::default()
+            },
+            visibility: Visibility::Hidden,
+            background_color: BackgroundColor(Color::Rgba {
+                red: 0.,
+                green: 0.,
+                blue: 0.,
+                alpha: 0.5,
+            }),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: font_assets.fira_sans.clone(),
+                            font_size: 18.0,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    ..Default::default()
+                })
+                .insert(ChatLogText);
+            parent
+                .spawn(TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: font_assets.fira_sans.clone(),
+                            font_size: 18.0,
+                            color: Color::GOLD,
+                        },
+                    ),
+                    ..Default::default()
+                })
+                .insert(ChatInputText);
+        })
+        .insert(Name::new("ChatUI"))
+        .insert(ChatUI);
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn chat_input(
+    mut char_evr: EventReader<ReceivedCharacter>,
+    keys: Res<Input<KeyCode>>,
+    time: Res<Time<Real>>,
+    profile: Res<PlayerProfile>,
+    mut chat: ResMut<ChatInput>,
+    mut log: ResMut<ChatLog>,
+    mut limiter: ResMut<ChatRateLimiter>,
+    mut socket: ResMut<MatchboxSocket<MultipleChannels>>,
+) {
+    let now = time.elapsed_seconds_f64();
+
+    if !chat.open {
+        // the key that opened the prompt should not end up in the message
+        char_evr.clear();
+        if keys.just_pressed(CHAT_OPEN_KEY) {
+            chat.open = true;
+        }
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Escape) {
+        chat.open = false;
+        chat.buffer.clear();
+        char_evr.clear();
+        return;
+    }
+
+    for ev in char_evr.read() {
+        if !ev.char.is_control() && chat.buffer.chars().count() < CHAT_MESSAGE_MAX_LEN {
+            chat.buffer.push(ev.char);
+        }
+    }
+    if keys.just_pressed(KeyCode::Back) {
+        chat.buffer.pop();
+    }
+    if !keys.just_pressed(KeyCode::Return) {
+        return;
+    }
+
+    chat.open = false;
+    let text = chat.buffer.trim().to_owned();
+    chat.buffer.clear();
+    if text.is_empty() {
+        return;
+    }
+    if !limiter.allow_local(now) {
+        log.notice("You are sending messages too fast", now);
+        return;
+    }
+
+    let msg = ChatMessage { text: text.clone() };
+    if let Some(packet) = msg.to_packet() {
+        let peers = socket.connected_peers().collect::<Vec<_>>();
+        for peer in peers {
+            if let Err(e) = socket.channel(CHAT_CHANNEL).try_send(packet.clone(), peer) {
+                warn!("unable to send chat message to {peer:?}: {e:?}");
+            }
+        }
+    }
+
+    let mut name = local_name(&profile);
+    if name.is_empty() {
+        name = "You".to_owned();
+    }
+    log.push(
+        ChatLine {
+            from: Some(name),
+            text,
+        },
+        now,
+    );
+}
+
+pub fn receive_chat(
+    time: Res<Time<Real>>,
+    room: Option<Res<LobbyRoom>>,
+    mut log: ResMut<ChatLog>,
+    mut limiter: ResMut<ChatRateLimiter>,
+    mut socket: ResMut<MatchboxSocket<MultipleChannels>>,
+) {
+    let now = time.elapsed_seconds_f64();
+
+    for (peer, packet) in socket.channel(CHAT_CHANNEL).receive() {
+        let msg = match ChatMessage::from_packet(&packet) {
+            Some(msg) => msg,
+            None => {
+                warn!("dropping invalid chat message from {peer:?}");
+                continue;
+            }
+        };
+        if !limiter.allow_peer(peer, now) {
+            debug!("dropping chat message from {peer:?}, rate limited");
+            continue;
+        }
+
+        let text = msg
+            .text
+            .trim()
+            .chars()
+            .take(CHAT_MESSAGE_MAX_LEN)
+            .collect::<String>();
+        let name = room
+            .as_ref()
+            .and_then(|r| r.names.get(&peer).cloned())
+            .filter(|n| !n.is_empty())
+            .unwrap_or_else(|| format!("{}", peer));
+        log.push(
+            ChatLine {
+                from: Some(name),
+                text,
+            },
+            now,
+        );
+    }
+}
+
+pub fn update_chat_ui(
+    time: Res<Time<Real>>,
+    app_state: Res<State<AppState>>,
+    chat: Res<ChatInput>,
+    log: Res<ChatLog>,
+    mut ui_query: Query<&mut Visibility, With<ChatUI>>,
+    mut log_query: Query<&mut Text, (With<ChatLogText>, Without<ChatInputText>)>,
+    mut input_query: Query<&mut Text, (With<ChatInputText>, Without<ChatLogText>)>,
+) {
+    let in_online_state = matches!(
+        app_state.get(),
+        AppState::MenuConnect | AppState::RoundOnline
+    );
+    let recent = time.elapsed_seconds_f64() - log.last_message < CHAT_FADE_SECS;
+    let show = in_online_state && (chat.open || (recent && !log.lines.is_empty()));
+
+    for mut vis in ui_query.iter_mut() {
+        *vis = if show {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+    for mut text in log_query.iter_mut() {
+        text.sections[0].value = log
+            .lines
+            .iter()
+            .map(|l| l.format())
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+    for mut text in input_query.iter_mut() {
+        text.sections[0].value = if chat.open {
+            format!("> {}_", chat.buffer)
+        } else {
+            format!("[{:?}] to chat", CHAT_OPEN_KEY)
+        };
+    }
+}
+
+pub fn close_chat(mut chat: ResMut<ChatInput>) {
+    chat.open = false;
+    chat.buffer.clear();
+}
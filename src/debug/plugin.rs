@@ -5,14 +5,17 @@ use bevy_inspector_egui::quick::WorldInspectorPlugin;
 
 use crate::{
     menu::connect::LocalHandle,
-    npc::components::EdibleTarget,
+    npc::components::{EdibleTarget, GooseBrain, GooseHealth, GoosePopulation},
+    physics::Position,
     player::input::GGRSConfig,
     player::{
         checksum::Checksum,
-        components::{Edible, EdibleSpawnTimer, Player, PlayerHealth},
-        resources::HealthBarsAdded,
+        components::{Edible, EdibleSpawnTimer, LastHitBy, Player, PlayerHealth, PlayerLives},
+        projectiles::Projectile,
+        resources::{HealthBarsAdded, PlayerNames, RoundScore},
+        status::StatusEffects,
     },
-    AppState, GameState,
+    AppState,
 };
 
 use super::components::{ConsoleReady, ConsoleUpdateTimer, EdibleCount, PeerInfo};
@@ -29,9 +32,18 @@ impl Plugin for DebugPlugin {
                 .register_type::<LocalHandle>()
                 .register_type::<EdibleTarget>()
                 .register_type::<Edible>()
+                .register_type::<GooseBrain>()
+                .register_type::<GooseHealth>()
+                .register_type::<GoosePopulation>()
                 .register_type::<EdibleSpawnTimer>()
                 .register_type::<Player>()
+                .register_type::<Position>()
                 .register_type::<PlayerHealth>()
+                .register_type::<Projectile>()
+                .register_type::<PlayerLives>()
+                .register_type::<LastHitBy>()
+                .register_type::<StatusEffects>()
+                .register_type::<RoundScore>()
                 .register_type::<HealthBarsAdded>();
         }
     }
@@ -42,7 +54,6 @@ pub struct ConsolePlugin;
 impl Plugin for ConsolePlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnExit(AppState::Loading), setup_ui)
-            .add_systems(Update, log_ggrs_events.run_if(in_state(GameState::Playing)))
             .add_systems(Update, open_console)
             .add_systems(
                 Update,
@@ -72,6 +83,7 @@ pub fn update_peer_info(
     time: Res<Time>,
     mut timer: ResMut<ConsoleUpdateTimer>,
     mut peer_info: ResMut<PeerInfo>,
+    names: Res<PlayerNames>,
 ) {
     // only update when timer finishes
     if timer.0.tick(time.delta()).just_finished() {
@@ -87,33 +99,27 @@ pub fn update_peer_info(
                             format!("{:?}", e)
                         }
                     };
-                    let line = format!("Player {:?}: {:?}", player_handle, stats);
+                    let line = format!(
+                        "{} ({:?}): {:?}",
+                        names.get(player_handle),
+                        player_handle,
+                        stats
+                    );
                     tmp.reserve(line.len() + 1);
-                    tmp.push_str("\n");
+                    tmp.push('\n');
                     tmp.push_str(&line);
-                    tmp.push_str("\n");
+                    tmp.push('\n');
                 }
             }
             Session::SyncTest(s) => {
                 let line = format!("Local players {}", s.num_players());
                 tmp.reserve(line.len() + 1);
-                tmp.push_str("\n");
+                tmp.push('\n');
                 tmp.push_str(&line);
-                tmp.push_str("\n");
+                tmp.push('\n');
             }
             _ => (),
         }
         peer_info.0 = tmp;
     }
 }
-
-pub fn log_ggrs_events(mut session: ResMut<Session<GGRSConfig>>) {
-    match session.as_mut() {
-        Session::P2P(s) => {
-            for event in s.events() {
-                info!("GGRS Event: {:?}", event);
-            }
-        }
-        _ => (),
-    }
-}
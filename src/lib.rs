@@ -1,24 +1,30 @@
 mod ascii;
 mod audio;
+pub mod chat;
 pub mod debug;
 mod graphics;
 mod loading;
 mod map;
 mod menu;
 pub mod npc;
+pub mod physics;
 pub mod player;
+pub mod stats;
 
 use crate::audio::InternalAudioPlugin;
 use crate::loading::LoadingPlugin;
 use ascii::AsciiPlugin;
 use bevy::prelude::*;
 use bevy::{app::App, diagnostic::FrameTimeDiagnosticsPlugin};
+use chat::plugin::ChatPlugin;
 use debug::plugin::{ConsolePlugin, DebugPlugin};
 use graphics::GraphicsPlugin;
 use map::tilemap::TileMapPlugin;
 use menu::plugin::MenuPlugin;
 use npc::plugin::GoosePlugin;
+use physics::PhysicsPlugin;
 use player::plugin::PlayerPlugin;
+use stats::plugin::StatsPlugin;
 
 #[cfg(debug_assertions)]
 use bevy::diagnostic::LogDiagnosticsPlugin;
@@ -27,6 +33,7 @@ pub const ASPECT_RATIO: f32 = 16.0 / 9.0;
 pub const MAP_HEIGHT: f32 = 768.0;
 pub const TILE_SIZE: f32 = 32.0;
 pub const HEALTH_BAR_Y_OFFSET: f32 = TILE_SIZE + 10.;
+pub const NAME_TAG_Y_OFFSET: f32 = HEALTH_BAR_Y_OFFSET + 14.;
 pub const FPS: usize = 60;
 pub const FIXED_TICK_MS: u64 = 1000 / FPS as u64; // use fixed duration tick delta to keep in sync with GGRSSchedule
 
@@ -52,6 +59,8 @@ pub enum AppState {
     MenuOnline,
     // Menu for setting options
     MenuOptions,
+    // Menu for viewing local player stats
+    MenuLeaderboard,
     // Game logic for online round is executed
     RoundOnline,
     // Game logic fo local round is executed
@@ -85,9 +94,12 @@ impl Plugin for GamePlugin {
                 TileMapPlugin,
                 MenuPlugin,
                 InternalAudioPlugin,
+                PhysicsPlugin,
                 PlayerPlugin,
                 GoosePlugin,
                 ConsolePlugin,
+                StatsPlugin,
+                ChatPlugin,
             ));
 
         #[cfg(debug_assertions)]
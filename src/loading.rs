@@ -1,3 +1,4 @@
+use crate::player::edibles::{EdibleRegistry, EdibleRegistryLoader};
 use crate::AppState;
 use bevy::prelude::*;
 use bevy_asset_loader::prelude::*;
@@ -10,12 +11,29 @@ pub struct LoadingPlugin;
 /// If interested, take a look at <https://bevy-cheatbook.github.io/features/assets.html>
 impl Plugin for LoadingPlugin {
     fn build(&self, app: &mut App) {
-        app.add_loading_state(
-            LoadingState::new(AppState::Loading).continue_to_state(AppState::MenuMain),
-        )
-        .add_collection_to_loading_state::<_, FontAssets>(AppState::Loading)
-        .add_collection_to_loading_state::<_, AudioAssets>(AppState::Loading)
-        .add_collection_to_loading_state::<_, TextureAssets>(AppState::Loading);
+        app.init_asset::<EdibleRegistry>()
+            .init_asset_loader::<EdibleRegistryLoader>()
+            .add_loading_state(
+                LoadingState::new(AppState::Loading).continue_to_state(AppState::MenuMain),
+            )
+            .add_collection_to_loading_state::<_, FontAssets>(AppState::Loading)
+            .add_collection_to_loading_state::<_, AudioAssets>(AppState::Loading)
+            .add_collection_to_loading_state::<_, TextureAssets>(AppState::Loading)
+            .add_collection_to_loading_state::<_, DataAssets>(AppState::Loading)
+            .add_systems(OnExit(AppState::Loading), insert_edible_registry);
+    }
+}
+
+/// The simulation reads edibles every frame, keep them in a resource instead of going through the asset.
+/// Without them the registry stays empty and matches refuse to start.
+fn insert_edible_registry(
+    mut commands: Commands,
+    data: Res<DataAssets>,
+    registries: Res<Assets<EdibleRegistry>>,
+) {
+    match registries.get(&data.edibles) {
+        Some(registry) => commands.insert_resource(registry.clone()),
+        None => error!("edible registry missing after loading, matches can't start"),
     }
 }
 
@@ -42,6 +60,16 @@ pub struct AudioAssets {
     pub sprinting: Handle<AudioSource>,
     #[asset(path = "audio/pickup.ogg")]
     pub pickup: Handle<AudioSource>,
+    #[asset(path = "audio/walking.ogg")]
+    pub poop_slip: Handle<AudioSource>,
+    #[asset(path = "audio/pickup.ogg")]
+    pub goose_honk: Handle<AudioSource>,
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct DataAssets {
+    #[asset(path = "edibles/base.edibles.ron")]
+    pub edibles: Handle<EdibleRegistry>,
 }
 
 #[derive(AssetCollection, Resource)]
@@ -70,6 +98,12 @@ pub struct TextureAssets {
     pub texture_fireball: Handle<Image>,
     #[asset(path = "textures/lettuce.png")]
     pub texture_lettuce: Handle<Image>,
+    #[asset(path = "textures/shell.png")]
+    pub texture_shell: Handle<Image>,
+    #[asset(path = "textures/ghost_pepper.png")]
+    pub texture_ghost_pepper: Handle<Image>,
+    #[asset(path = "textures/blueberry.png")]
+    pub texture_blueberry: Handle<Image>,
     #[asset(path = "textures/goose.png")]
     pub texture_goose: Handle<Image>,
     // map textures
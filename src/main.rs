@@ -8,14 +8,25 @@ use bevy::winit::WinitWindows;
 use bevy::DefaultPlugins;
 use bevy_ggrs::{GgrsApp, GgrsPlugin, ReadInputs};
 use std::io::Cursor;
-use turtle_time::npc::components::{EdibleTarget, Goose, HasTarget};
+use turtle_time::npc::components::{
+    EdibleTarget, Goose, GooseBrain, GooseHealth, GooseHitFlash, GoosePopulation, HasTarget,
+};
+use turtle_time::physics::Position;
 use turtle_time::player::checksum::Checksum;
 use turtle_time::player::components::{
-    Edible, EdibleSpawnTimer, Expired, Fireball, FireballAmmo, FireballMovement, FireballReady,
-    FireballTimer, Player, PlayerHealth, PlayerHealthBar, PlayerPoop, PlayerPoopTimer, PlayerSpeed,
-    PlayerSpeedBoost, RoundComponent,
+    BashCooldown, Edible, EdibleSpawnTimer, Expired, FireballAmmo, FireballReady, KillFeedEntry,
+    LastHitBy, Player, PlayerHealth, PlayerHealthBar, PlayerInvulnerable, PlayerLives, PlayerPoop,
+    PlayerPoopTimer, PlayerRespawnTimer, PlayerSpeed, PlayerSpeedBoost, PlayerVelocity, PoopAmmo,
+    PoopReady, RoundComponent,
 };
 use turtle_time::player::input::{input, GGRSConfig, PlayerControls};
+use turtle_time::player::projectiles::{
+    Projectile, ProjectileHoming, ProjectileMovement, ProjectileTimer,
+};
+use turtle_time::player::resources::{AgreedRandom, RoundScore, RoundTimer, Spectator};
+use turtle_time::player::spectate::spectator_input;
+use turtle_time::player::status::StatusEffects;
+use turtle_time::stats::resources::RoundStats;
 use turtle_time::{GamePlugin, ASPECT_RATIO, FPS, MAP_HEIGHT};
 use winit::window::Icon;
 
@@ -25,29 +36,54 @@ fn main() {
     // TODO: move GGRS plugin setup out of mains
     app.add_plugins(GgrsPlugin::<GGRSConfig>::default())
         .set_rollback_schedule_fps(FPS)
-        .add_systems(ReadInputs, input)
+        .add_systems(
+            ReadInputs,
+            (
+                input.run_if(not(resource_exists::<Spectator>())),
+                spectator_input.run_if(resource_exists::<Spectator>()),
+            ),
+        )
+        .rollback_component_with_clone::<BashCooldown>()
         .rollback_component_with_clone::<Checksum>()
         .rollback_component_with_clone::<Edible>()
         .rollback_component_with_clone::<EdibleTarget>()
         .rollback_component_with_clone::<Expired>()
-        .rollback_component_with_clone::<Fireball>()
         .rollback_component_with_clone::<FireballAmmo>()
         .rollback_component_with_clone::<FireballReady>()
-        .rollback_component_with_clone::<FireballMovement>()
-        .rollback_component_with_clone::<FireballTimer>()
         .rollback_component_with_clone::<Goose>()
+        .rollback_component_with_clone::<GooseBrain>()
+        .rollback_component_with_clone::<GooseHealth>()
+        .rollback_component_with_clone::<GooseHitFlash>()
+        .rollback_component_with_clone::<KillFeedEntry>()
+        .rollback_component_with_clone::<LastHitBy>()
         .rollback_component_with_clone::<HasTarget>()
         .rollback_component_with_clone::<Player>()
         .rollback_component_with_clone::<PlayerHealth>()
         .rollback_component_with_clone::<PlayerHealthBar>()
+        .rollback_component_with_clone::<PlayerInvulnerable>()
+        .rollback_component_with_clone::<PlayerLives>()
         .rollback_component_with_clone::<PlayerSpeed>()
         .rollback_component_with_clone::<PlayerSpeedBoost>()
+        .rollback_component_with_clone::<PlayerVelocity>()
         .rollback_component_with_clone::<PlayerControls>()
         .rollback_component_with_clone::<PlayerPoop>()
         .rollback_component_with_clone::<PlayerPoopTimer>()
+        .rollback_component_with_clone::<PoopAmmo>()
+        .rollback_component_with_clone::<PoopReady>()
+        .rollback_component_with_clone::<PlayerRespawnTimer>()
+        .rollback_component_with_clone::<Position>()
+        .rollback_component_with_clone::<Projectile>()
+        .rollback_component_with_clone::<ProjectileHoming>()
+        .rollback_component_with_clone::<ProjectileMovement>()
+        .rollback_component_with_clone::<ProjectileTimer>()
         .rollback_component_with_clone::<RoundComponent>()
-        .rollback_component_with_clone::<Transform>()
-        .rollback_resource_with_clone::<EdibleSpawnTimer>();
+        .rollback_component_with_clone::<StatusEffects>()
+        .rollback_resource_with_clone::<AgreedRandom>()
+        .rollback_resource_with_clone::<EdibleSpawnTimer>()
+        .rollback_resource_with_clone::<GoosePopulation>()
+        .rollback_resource_with_clone::<RoundScore>()
+        .rollback_resource_with_clone::<RoundStats>()
+        .rollback_resource_with_clone::<RoundTimer>();
 
     app.insert_resource(Msaa::Off)
         .insert_resource(ClearColor(Color::rgb(0.0, 0.3, 0.0)))
@@ -1,3 +1,5 @@
+use crate::menu::handshake::stable_hash;
+
 pub const ASCII_MAP: &str = "
 &&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&
 &&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&
@@ -34,3 +36,25 @@ pub const ASCII_MAP: &str = "
 &&&|~~~~~~~..........................~~~~~~~|&&&
 &&&%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%&&&
 &&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&";
+
+/// A map the lobby host can pick
+pub struct MapDef {
+    pub name: &'static str,
+    pub layout: &'static str,
+}
+
+/// Every map in this build, `MatchSettings::map` indexes into it
+pub const MAPS: &[MapDef] = &[MapDef {
+    name: "Peanut Pond",
+    layout: ASCII_MAP,
+}];
+
+/// Stable hash of every map name and layout, peers compare it before playing together
+pub fn map_hash() -> u64 {
+    let mut bytes = Vec::new();
+    for map in MAPS {
+        bytes.extend_from_slice(map.name.as_bytes());
+        bytes.extend_from_slice(map.layout.as_bytes());
+    }
+    stable_hash(&bytes)
+}
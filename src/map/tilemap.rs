@@ -1,18 +1,23 @@
-use super::map::ASCII_MAP;
+use super::map::MAPS;
+use crate::physics::{FixedVec2, Position};
+use crate::player::resources::MatchSettings;
 use crate::{loading::TextureAssets, AppState, GameState, TILE_SIZE};
 use bevy::prelude::*;
 
 pub struct TileMapPlugin;
 
+/// Root of the spawned tiles, `index` is the entry in `MAPS` they were built from
 #[derive(Component)]
-struct Map;
+struct Map {
+    index: usize,
+}
 
 #[derive(Component)]
 pub struct EncounterSpawner;
 
 #[derive(Component)]
 pub struct PlayerSpawn {
-    pub pos: Vec3,
+    pub pos: FixedVec2,
 }
 
 #[derive(Component)]
@@ -21,6 +26,8 @@ pub struct TileCollider;
 impl Plugin for TileMapPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnExit(AppState::Loading), Self::spawn_map)
+            .add_systems(OnEnter(AppState::RoundOnline), Self::load_selected_map)
+            .add_systems(OnEnter(AppState::RoundLocal), Self::load_selected_map)
             .add_systems(OnEnter(GameState::Playing), Self::show_map)
             .add_systems(OnEnter(GameState::Paused), Self::hide_map);
     }
@@ -53,9 +60,34 @@ impl TileMapPlugin {
         }
     }
 
-    fn spawn_map(mut commands: Commands, textures: Res<TextureAssets>) {
+    fn spawn_map(
+        mut commands: Commands,
+        textures: Res<TextureAssets>,
+        settings: Res<MatchSettings>,
+    ) {
+        Self::spawn_layout(&mut commands, &textures, settings.map);
+    }
+
+    /// Swaps the tiles when the host picked a different map than the one spawned
+    fn load_selected_map(
+        mut commands: Commands,
+        textures: Res<TextureAssets>,
+        settings: Res<MatchSettings>,
+        map_query: Query<(Entity, &Map)>,
+    ) {
+        if let Ok((entity, map)) = map_query.get_single() {
+            if map.index == settings.map {
+                return;
+            }
+            commands.entity(entity).despawn_recursive();
+        }
+        Self::spawn_layout(&mut commands, &textures, settings.map);
+    }
+
+    fn spawn_layout(commands: &mut Commands, textures: &TextureAssets, index: usize) {
         let mut tiles = Vec::new();
-        let map = ASCII_MAP.to_string();
+        let index = index.min(MAPS.len() - 1);
+        let map = MAPS[index].layout.to_string();
 
         let lines = map.split("\n");
         let top: usize = 0;
@@ -103,6 +135,8 @@ impl TileMapPlugin {
                     ..Default::default()
                 };
                 let tile = commands.spawn(sprite).id();
+                // tiles never move, the simulation reads the fixed-point copy of their position
+                let position = FixedVec2::from_vec2(translation.truncate());
                 if char == '|'
                     || char == '='
                     || char == '_'
@@ -111,17 +145,19 @@ impl TileMapPlugin {
                     || char == '$'
                 {
                     // Walls
-                    commands.entity(tile).insert(TileCollider);
+                    commands
+                        .entity(tile)
+                        .insert((TileCollider, Position(position)));
                 }
                 if char == '~' {
                     // Grass
-                    commands.entity(tile).insert(EncounterSpawner);
+                    commands
+                        .entity(tile)
+                        .insert((EncounterSpawner, Position(position)));
                 }
                 if char == '!' {
                     // Player Spawn
-                    commands
-                        .entity(tile)
-                        .insert(PlayerSpawn { pos: translation });
+                    commands.entity(tile).insert(PlayerSpawn { pos: position });
                 }
                 tiles.push(tile);
             }
@@ -132,7 +168,7 @@ impl TileMapPlugin {
                 visibility: Visibility::Hidden,
                 ..Default::default()
             })
-            .insert(Map)
+            .insert(Map { index })
             .insert(Name::new("Map"))
             .insert(Transform::default())
             .insert(GlobalTransform::default())
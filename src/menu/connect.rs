@@ -1,28 +1,75 @@
+use super::lobby::{local_name, GgrsChannel, LobbyRoom, RoundInProgress, GGRS_CHANNEL};
 use super::online::PlayerCount;
 use super::plugin::{BUTTON_TEXT, HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON};
 use crate::loading::FontAssets;
+use crate::map::map::MAPS;
+use crate::npc::components::GOOSE_MAX_MAX;
 use crate::player::input::GGRSConfig;
-use crate::player::resources::AgreedRandom;
-use crate::{AppState, GameState, FPS, INPUT_DELAY, MATCHBOX_ADDR, MAX_PREDICTION};
+use crate::player::resources::{
+    AgreedRandom, InputHistory, MatchSettings, PlayerNames, Spectator, MAX_PREDICTION_MAX,
+    MAX_PREDICTION_MIN, PLAYER_LIVES_MAX, PLAYER_LIVES_MIN, ROUND_TIME_LIMIT_MAX,
+    ROUND_TIME_LIMIT_MIN,
+};
+use crate::stats::profile::PlayerProfile;
+use crate::{AppState, GameState, FPS, MATCHBOX_ADDR};
 use bevy::prelude::*;
 use bevy_ggrs::Session;
 use bevy_inspector_egui::prelude::ReflectInspectorOptions;
 use bevy_inspector_egui::InspectorOptions;
-use bevy_matchbox::prelude::{PeerState, SingleChannel};
+use bevy_matchbox::matchbox_socket::{self, ChannelConfig, MessageLoopFuture, WebRtcSocketBuilder};
+use bevy_matchbox::prelude::MultipleChannels;
 use bevy_matchbox::MatchboxSocket;
 use ggrs::{PlayerType, SessionBuilder};
+use std::sync::{Arc, Mutex};
+
+const ROUND_TIME_LIMIT_STEP: u32 = 30;
 
 #[derive(Component)]
 pub struct MenuConnectUI;
 
 #[derive(Component)]
 pub enum MenuConnectBtn {
+    Ready,
+    Mode,
+    Map,
+    LivesUp,
+    LivesDown,
+    TimeUp,
+    TimeDown,
+    GeeseUp,
+    GeeseDown,
+    DelayUp,
+    DelayDown,
+    PredictionUp,
+    PredictionDown,
+    Retry,
     Back,
 }
 
 #[derive(Component)]
 pub struct LobbyText;
 
+#[derive(Component)]
+pub struct LobbyPeersText;
+
+#[derive(Component)]
+pub struct LobbySettingsText;
+
+#[derive(Component)]
+pub struct ReadyBtnText;
+
+/// Settings buttons, only shown to the lobby host
+#[derive(Component)]
+pub struct HostControls;
+
+/// Ready button, hidden once the connection failed
+#[derive(Component)]
+pub struct ReadyControls;
+
+/// Retry button, only shown once the connection failed
+#[derive(Component)]
+pub struct RetryControls;
+
 #[derive(Resource, Reflect, Default, InspectorOptions)]
 #[reflect(Resource, InspectorOptions)]
 pub struct LocalHandle(pub usize);
@@ -32,91 +79,550 @@ pub struct ConnectData {
     pub lobby_id: String,
 }
 
-pub fn create_matchbox_socket(mut commands: Commands, connect_data: Res<ConnectData>) {
+/// Give up when the signaling server hasn't assigned us an id by then
+pub const SIGNALING_TIMEOUT_SECS: f64 = 10.;
+/// Give up when the lobby made no progress for this long, e.g. nobody joined or left
+/// or a peer never sent its name
+pub const LOBBY_TIMEOUT_SECS: f64 = 120.;
+/// Give up on a peer that sent nothing, not even a ping, for this long
+pub const PEER_SILENCE_TIMEOUT_SECS: f64 = 15.;
+
+/// Everything that can go wrong before the match starts, shown in the connect menu
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConnectError {
+    SignalingUnreachable,
+    SignalingLost,
+    PeerConnectionFailed,
+    LobbyFull,
+    Timeout,
+    /// A connected peer stopped sending anything, holds their name
+    PeerSilent(String),
+    /// The edibles file didn't load, peers would have nothing to agree on
+    EdiblesMissing,
+    Session(String),
+}
+
+impl ConnectError {
+    pub fn message(&self) -> String {
+        match self {
+            ConnectError::SignalingUnreachable => {
+                "Can't reach the matchmaking server,\ncheck your connection".to_owned()
+            }
+            ConnectError::SignalingLost => {
+                "Lost the connection to the matchmaking server".to_owned()
+            }
+            ConnectError::PeerConnectionFailed => {
+                "Couldn't connect to another player,\na firewall may be blocking WebRTC".to_owned()
+            }
+            ConnectError::LobbyFull => "This lobby is already full".to_owned(),
+            ConnectError::Timeout => "Timed out waiting for other players".to_owned(),
+            ConnectError::PeerSilent(name) => format!("{} stopped responding", name),
+            ConnectError::EdiblesMissing => {
+                "The edibles file is missing or invalid,\nreinstall the game".to_owned()
+            }
+            ConnectError::Session(e) => format!("Unable to start the match:\n{}", e),
+        }
+    }
+}
+
+/// Progress and errors of the current connection attempt
+#[derive(Default, Resource)]
+pub struct ConnectStatus {
+    pub error: Option<ConnectError>,
+    pub attempt: u32,
+    /// Peers that left before their WebRTC connection was established
+    pub failed_peers: u32,
+    started_at: Option<f64>,
+    last_progress: f64,
+    /// Filled in by the socket's message loop when it ends with an error
+    socket_error: Arc<Mutex<Option<ConnectError>>>,
+}
+
+impl ConnectStatus {
+    pub fn fail(&mut self, error: ConnectError) {
+        if self.error.is_none() {
+            warn!("connection failed: {:?}", error);
+            self.error = Some(error);
+        }
+    }
+
+    /// The socket's message loop ended, report why if it told us
+    pub fn socket_closed(&mut self) {
+        let error = self.socket_error.lock().unwrap().take();
+        self.fail(error.unwrap_or(ConnectError::SignalingLost));
+    }
+
+    /// Anything that shows the lobby is alive, like players joining or leaving
+    pub fn progress(&mut self, now: f64) {
+        self.last_progress = now;
+    }
+}
+
+pub fn create_matchbox_socket(
+    mut commands: Commands,
+    connect_data: Res<ConnectData>,
+    profile: Res<PlayerProfile>,
+) {
+    open_socket(&mut commands, &connect_data, &profile, 0);
+}
+
+fn open_socket(
+    commands: &mut Commands,
+    connect_data: &ConnectData,
+    profile: &PlayerProfile,
+    attempt: u32,
+) {
     let lobby_id = &connect_data.lobby_id;
     let room_url = format!("{MATCHBOX_ADDR}/{lobby_id}");
     info!("connecting to matchbox server: {:?}", room_url);
 
     // remove old socket that may exist from previous round
-    commands.remove_resource::<MatchboxSocket<SingleChannel>>();
-    // insert new socket resource for next session
-    commands.insert_resource(MatchboxSocket::new_reliable(room_url));
+    commands.remove_resource::<MatchboxSocket<MultipleChannels>>();
+    // insert new socket resource for next session, GGRS gets the first channel,
+    // followed by the lobby and chat channels
+    let (socket, message_loop) = WebRtcSocketBuilder::new(room_url)
+        .add_channel(ChannelConfig::reliable())
+        .add_reliable_channel()
+        .add_reliable_channel()
+        .build();
+
+    // keep the reason the message loop stopped, the socket itself only tells us it closed
+    let status = ConnectStatus {
+        attempt,
+        ..Default::default()
+    };
+    let socket_error = status.socket_error.clone();
+    let message_loop: MessageLoopFuture = Box::pin(async move {
+        let result = message_loop.await;
+        if let Err(e) = &result {
+            let error = match e {
+                matchbox_socket::Error::ConnectionFailed(_) => ConnectError::SignalingUnreachable,
+                matchbox_socket::Error::Disconnected(_) => ConnectError::SignalingLost,
+            };
+            *socket_error.lock().unwrap() = Some(error);
+        }
+        result
+    });
+    commands.insert_resource(MatchboxSocket::from((socket, message_loop)));
+    commands.insert_resource(status);
+    commands.remove_resource::<Spectator>();
+    // the netcode preferences only matter if we end up hosting
+    commands.insert_resource(LobbyRoom::new(MatchSettings {
+        netcode: profile.netcode.clone(),
+        ..Default::default()
+    }));
     // commands.remove_resource::<ConnectData>();
 }
 
+/// Leaving to the main menu drops the connection, so peers waiting on a rematch see us leave
+pub fn close_socket(mut commands: Commands) {
+    commands.remove_resource::<MatchboxSocket<MultipleChannels>>();
+    commands.remove_resource::<LobbyRoom>();
+    commands.remove_resource::<Spectator>();
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn lobby_system(
     mut commands: Commands,
-    mut socket: ResMut<MatchboxSocket<SingleChannel>>,
+    time: Res<Time<Real>>,
+    socket: Option<ResMut<MatchboxSocket<MultipleChannels>>>,
+    mut room: ResMut<LobbyRoom>,
+    mut status: ResMut<ConnectStatus>,
     mut app_state: ResMut<NextState<AppState>>,
     mut game_state: ResMut<NextState<GameState>>,
     player_count: Res<PlayerCount>,
+    profile: Res<PlayerProfile>,
     mut query: Query<&mut Text, With<LobbyText>>,
 ) {
-    // regularly call update_peers to update the list of connected peers
-    for (peer, new_state) in socket.update_peers() {
-        // you can also handle the specific dis(connections) as they occur:
-        match new_state {
-            PeerState::Connected => info!("peer {peer:?} connected"),
-            PeerState::Disconnected => info!("peer {peer:?} disconnected"),
+    let now = time.elapsed_seconds_f64();
+    let started_at = *status.started_at.get_or_insert(now);
+    if status.last_progress < started_at {
+        status.last_progress = started_at;
+    }
+
+    let total = room.peers.len() + 1;
+    let remaining = player_count.0.saturating_sub(total);
+    let overfull = total > player_count.0;
+    // readying up is up to the players, every other stage should move on by itself
+    let readying_up = remaining == 0
+        && !overfull
+        && room.everyone_greeted()
+        && room.everyone_named()
+        && room.settings_agreed();
+    let silent = room.silent_peer(now, PEER_SILENCE_TIMEOUT_SECS);
+
+    if status.error.is_none() {
+        if room.local_id.is_none() && now - started_at > SIGNALING_TIMEOUT_SECS {
+            status.fail(ConnectError::SignalingUnreachable);
+        } else if let Some(peer) = silent {
+            status.fail(ConnectError::PeerSilent(room.peer_name(peer)));
+        } else if !readying_up && now - status.last_progress > LOBBY_TIMEOUT_SECS {
+            let error = if status.failed_peers > 0 {
+                ConnectError::PeerConnectionFailed
+            } else {
+                ConnectError::Timeout
+            };
+            status.fail(error);
+        } else if overfull && room.everyone_greeted() && !room.seated(player_count.0) {
+            status.fail(ConnectError::LobbyFull);
         }
     }
 
-    let connected_peers = socket.connected_peers().count();
-    let remaining = player_count.0 - (connected_peers + 1);
-    query.single_mut().sections[0].value = format!("Waiting for {remaining} more player(s)",);
-    if remaining > 0 {
+    let mut socket = match (socket, &status.error) {
+        (Some(_), Some(_)) => {
+            // drop the connection so the other players see us leave
+            commands.remove_resource::<MatchboxSocket<MultipleChannels>>();
+            None
+        }
+        (socket, _) => socket,
+    };
+
+    let incompatible = room
+        .incompatible_peer()
+        .map(|(peer, reason)| format!("Can't play together: {} {}", room.peer_name(peer), reason));
+    let text = if let Some(error) = &status.error {
+        format!("{}\n", error.message())
+    } else if let Some(error) = incompatible.clone() {
+        error
+    } else if room.local_id.is_none() && status.attempt > 0 {
+        format!(
+            "Connecting to the matchmaking server (attempt {})...",
+            status.attempt + 1
+        )
+    } else if room.local_id.is_none() {
+        "Connecting to the matchmaking server...".to_owned()
+    } else if room.current_round.is_some() {
+        "Round in progress, joining as a spectator...".to_owned()
+    } else if remaining > 0 {
+        format!("Waiting for {remaining} more player(s)")
+    } else if overfull {
+        "Lobby is full, waiting for extra players to leave...".to_owned()
+    } else if !room.everyone_greeted() {
+        "Checking game versions...".to_owned()
+    } else if !room.everyone_named() {
+        "Exchanging player names...".to_owned()
+    } else if !room.settings_agreed() {
+        "Waiting for the host's settings...".to_owned()
+    } else if !room.everyone_ready() {
+        "Waiting for everyone to ready up".to_owned()
+    } else {
+        "Starting match!".to_owned()
+    };
+    for mut t in query.iter_mut() {
+        t.sections[0].value = text.clone();
+    }
+
+    let socket = match socket.as_mut() {
+        Some(socket) => socket,
+        None => return,
+    };
+
+    // the players told us they are mid-round, watch it and play the next one
+    if room.current_round.is_some() {
+        if status.error.is_some() || incompatible.is_some() || !room.everyone_greeted() {
+            return;
+        }
+        match start_spectating(&mut commands, &mut room) {
+            Ok(()) => {
+                app_state.set(AppState::RoundOnline);
+                game_state.set(GameState::Playing);
+            }
+            Err(e) => status.fail(ConnectError::Session(e)),
+        }
         return;
     }
 
-    // set final player list
-    let players = socket.players();
+    if status.error.is_some()
+        || incompatible.is_some()
+        || remaining > 0
+        || overfull
+        || !room.everyone_greeted()
+        || !room.everyone_named()
+        || !room.settings_agreed()
+        || !room.everyone_ready()
+    {
+        return;
+    }
 
-    let mut peers = Vec::new();
-    for p in players.clone() {
-        match p {
-            PlayerType::Remote(id) => peers.push(id),
-            PlayerType::Spectator(id) => peers.push(id),
-            PlayerType::Local => (),
+    match start_p2p_session(&mut commands, socket, &mut room, &profile) {
+        Ok(true) => {
+            app_state.set(AppState::RoundOnline);
+            game_state.set(GameState::Playing);
         }
+        Ok(false) => (),
+        Err(e) => status.fail(ConnectError::Session(e)),
     }
-    // if we made it here we should have a local peer ID
-    match socket.id() {
-        Some(id) => peers.push(id),
-        None => (), // TODO: something more reliable
+}
+
+/// Builds the GGRS session for everyone in the room, used for the first round and every rematch.
+/// Returns false while the signalling server hasn't assigned our id yet.
+pub fn start_p2p_session(
+    commands: &mut Commands,
+    socket: &mut MatchboxSocket<MultipleChannels>,
+    room: &mut LobbyRoom,
+    profile: &PlayerProfile,
+) -> Result<bool, String> {
+    // set final player list, ordered by peer id so every peer agrees on handles
+    let local_id = match room.local_id {
+        Some(id) => id,
+        None => return Ok(false), // still waiting for the server to assign our id
     };
+    let mut ids = room
+        .peers
+        .iter()
+        .copied()
+        .chain(std::iter::once(local_id))
+        .collect::<Vec<_>>();
+    ids.sort();
+    let players = ids
+        .iter()
+        .map(|id| {
+            if *id == local_id {
+                PlayerType::Local
+            } else {
+                PlayerType::Remote(*id)
+            }
+        })
+        .collect::<Vec<_>>();
+    let names = ids
+        .iter()
+        .map(|id| {
+            if *id == local_id {
+                local_name(profile)
+            } else {
+                room.names.get(id).cloned().unwrap_or_default()
+            }
+        })
+        .collect::<Vec<_>>();
 
-    // Create GGRS P2P Session
+    // Create GGRS P2P Session, the netcode settings come from the host so every peer agrees
+    let netcode = &room.settings.netcode;
     let mut sess_build = SessionBuilder::<GGRSConfig>::new()
-        .with_num_players(player_count.0)
-        .with_max_prediction_window(MAX_PREDICTION)
-        .expect("Invalid MAX_PREDICTION")
+        .with_num_players(ids.len())
+        .with_max_prediction_window(netcode.max_prediction)
+        .map_err(|e| format!("invalid max prediction: {}", e))?
         .with_desync_detection_mode(ggrs::DesyncDetection::On { interval: 10 })
         .with_fps(FPS)
-        .expect("Invalid FPS")
-        .with_input_delay(INPUT_DELAY);
+        .map_err(|e| format!("invalid fps: {}", e))?
+        .with_input_delay(netcode.input_delay());
 
+    let mut local_handle = None;
     for (i, player_type) in players.into_iter().enumerate() {
         if player_type == PlayerType::Local {
             info!("Adding local player {}", i);
-            commands.insert_resource(LocalHandle(i));
+            local_handle = Some(i);
         } else {
             info!("Adding remote player {}", i)
         }
         sess_build = sess_build
             .add_player(player_type.clone(), i)
-            .expect("Invalid player added.");
+            .map_err(|e| format!("unable to add player {}: {}", i, e))?;
     }
 
-    // Start P2P session
-    let channel = socket.take_channel(0).unwrap();
+    // Start P2P session, later rounds reuse the channel taken for the first one
+    let channel = match room.ggrs_channel.clone() {
+        Some(channel) => {
+            channel.drain();
+            channel
+        }
+        None => {
+            let channel = socket
+                .take_channel(GGRS_CHANNEL)
+                .map_err(|e| format!("game channel unavailable: {:?}", e))?;
+            let channel = GgrsChannel::new(channel);
+            room.ggrs_channel = Some(channel.clone());
+            channel
+        }
+    };
     let sess = sess_build
         .start_p2p_session(channel)
-        .expect("Session could not be created.");
+        .map_err(|e| format!("session could not be created: {}", e))?;
 
+    info!("starting match with settings {:?}", room.settings);
+    if let Some(handle) = local_handle {
+        commands.insert_resource(LocalHandle(handle));
+    }
+    // spectators of the last round play in this one
+    commands.remove_resource::<Spectator>();
+    commands.insert_resource(InputHistory::default());
+    room.start_round(RoundInProgress {
+        round: room.round,
+        players: ids.clone(),
+        names: names.clone(),
+        settings: room.settings.clone(),
+    });
     commands.insert_resource(Session::P2P(sess));
-    commands.insert_resource(AgreedRandom::new(peers));
-    app_state.set(AppState::RoundOnline);
-    game_state.set(GameState::Playing);
+    commands.insert_resource(PlayerCount(ids.len()));
+    commands.insert_resource(AgreedRandom::new(ids, room.round));
+    commands.insert_resource(PlayerNames(names));
+    commands.insert_resource(room.settings.clone());
+    room.round += 1;
+    Ok(true)
+}
+
+/// Replays the round that was running when we joined from the confirmed inputs the players
+/// stream to us. SyncTest takes every input as local, so the history just feeds it all.
+fn start_spectating(commands: &mut Commands, room: &mut LobbyRoom) -> Result<(), String> {
+    let round = match room.current_round.clone() {
+        Some(round) => round,
+        None => return Err("no round to spectate".to_owned()),
+    };
+    let mut sess_build = SessionBuilder::<GGRSConfig>::new()
+        .with_num_players(round.players.len())
+        .with_max_prediction_window(round.settings.netcode.max_prediction)
+        .map_err(|e| format!("invalid max prediction: {}", e))?
+        .with_fps(FPS)
+        .map_err(|e| format!("invalid fps: {}", e))?
+        // recorded inputs already include the players' input delay and never need rolling back
+        .with_input_delay(0)
+        .with_check_distance(0);
+    for i in 0..round.players.len() {
+        sess_build = sess_build
+            .add_player(PlayerType::Local, i)
+            .map_err(|e| format!("unable to add player {}: {}", i, e))?;
+    }
+    let sess = sess_build
+        .start_synctest_session()
+        .map_err(|e| format!("session could not be created: {}", e))?;
+
+    info!(
+        "spectating round {} with settings {:?}",
+        round.round, round.settings
+    );
+    commands.insert_resource(Session::SyncTest(sess));
+    commands.insert_resource(Spectator::default());
+    // follow the first player, the HUD and camera need someone to show
+    commands.insert_resource(LocalHandle(0));
+    commands.insert_resource(PlayerCount(round.players.len()));
+    commands.insert_resource(AgreedRandom::new(round.players, round.round));
+    commands.insert_resource(PlayerNames(round.names));
+    commands.insert_resource(round.settings);
+    Ok(())
+}
+
+type SettingsTextFilter = (
+    With<LobbySettingsText>,
+    Without<LobbyPeersText>,
+    Without<ReadyBtnText>,
+);
+type ReadyTextFilter = (
+    With<ReadyBtnText>,
+    Without<LobbyPeersText>,
+    Without<LobbySettingsText>,
+);
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_lobby_ui(
+    room: Res<LobbyRoom>,
+    status: Res<ConnectStatus>,
+    profile: Res<PlayerProfile>,
+    mut peers_query: Query<&mut Text, (With<LobbyPeersText>, Without<LobbySettingsText>)>,
+    mut settings_query: Query<&mut Text, SettingsTextFilter>,
+    mut ready_query: Query<&mut Text, ReadyTextFilter>,
+    mut host_query: Query<&mut Visibility, With<HostControls>>,
+    mut ready_btn_query: Query<&mut Style, (With<ReadyControls>, Without<RetryControls>)>,
+    mut retry_query: Query<&mut Style, (With<RetryControls>, Without<ReadyControls>)>,
+) {
+    let failed = status.error.is_some();
+    let host = room.host();
+    let ready_label = |ready: bool| if ready { "ready" } else { "not ready" };
+
+    let mut lines = vec![];
+    if let Some(local_id) = room.local_id {
+        let mut name = local_name(&profile);
+        if name.is_empty() {
+            name = "You".to_owned();
+        }
+        let crown = if host == Some(local_id) {
+            " (host)"
+        } else {
+            ""
+        };
+        lines.push(format!(
+            "{}{} - {}",
+            name,
+            crown,
+            ready_label(room.local_ready)
+        ));
+    }
+    for peer in room.peers.iter() {
+        let name = room.names.get(peer).cloned().unwrap_or("...".to_owned());
+        let crown = if host == Some(*peer) { " (host)" } else { "" };
+        let ready = room.ready.get(peer).copied().unwrap_or_default();
+        let ping = match room.pings.get(peer) {
+            Some(ping) => format!(" ({:.0}ms)", ping),
+            None => "".to_owned(),
+        };
+        lines.push(format!(
+            "{}{}{} - {}",
+            name,
+            crown,
+            ping,
+            ready_label(ready)
+        ));
+    }
+
+    for mut text in peers_query.iter_mut() {
+        text.sections[0].value = lines.join("\n");
+    }
+    for mut text in settings_query.iter_mut() {
+        text.sections[0].value = room.settings.describe();
+    }
+    for mut text in ready_query.iter_mut() {
+        text.sections[0].value = if room.local_ready {
+            "Unready".to_owned()
+        } else {
+            "Ready".to_owned()
+        };
+    }
+    for mut vis in host_query.iter_mut() {
+        *vis = if room.is_host() && !failed {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+    for mut style in ready_btn_query.iter_mut() {
+        style.display = if failed { Display::None } else { Display::Flex };
+    }
+    for mut style in retry_query.iter_mut() {
+        style.display = if failed { Display::Flex } else { Display::None };
+    }
+}
+
+fn spawn_small_btn(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    label: &str,
+    btn: MenuConnectBtn,
+) {
+    parent
+        .spawn(ButtonBundle {
+            style: Style {
+                width: Val::Px(110.0),
+                height: Val::Px(50.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                margin: UiRect::all(Val::Px(8.)),
+                ..Default::default()
+            },
+            background_color: BackgroundColor(NORMAL_BUTTON),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent.spawn(TextBundle {
+                text: Text::from_section(
+                    label,
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 28.0,
+                        color: BUTTON_TEXT,
+                    },
+                ),
+                ..Default::default()
+            });
+        })
+        .insert(btn);
 }
 
 pub fn setup_ui(mut commands: Commands, font_assets: Res<FontAssets>) {
@@ -165,6 +671,151 @@ pub fn setup_ui(mut commands: Commands, font_assets: Res<FontAssets>) {
                 })
                 .insert(LobbyText);
 
+            // connected players and their ready state
+            parent
+                .spawn(TextBundle {
+                    style: Style {
+                        margin: UiRect::all(Val::Px(16.)),
+                        ..Default::default()
+                    },
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: font_assets.fira_sans.clone(),
+                            font_size: 28.,
+                            color: BUTTON_TEXT,
+                        },
+                    ),
+                    ..Default::default()
+                })
+                .insert(LobbyPeersText);
+
+            // match settings picked by the host
+            parent
+                .spawn(TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: font_assets.fira_sans.clone(),
+                            font_size: 28.,
+                            color: Color::GOLD,
+                        },
+                    ),
+                    ..Default::default()
+                })
+                .insert(LobbySettingsText);
+
+            // host settings buttons
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..Default::default()
+                    },
+                    visibility: Visibility::Hidden,
+                    background_color: BackgroundColor(Color::NONE),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    let font = &font_assets.fira_sans;
+                    spawn_small_btn(parent, font, "Mode", MenuConnectBtn::Mode);
+                    spawn_small_btn(parent, font, "Map", MenuConnectBtn::Map);
+                    spawn_small_btn(parent, font, "Lives -", MenuConnectBtn::LivesDown);
+                    spawn_small_btn(parent, font, "Lives +", MenuConnectBtn::LivesUp);
+                    spawn_small_btn(parent, font, "Time -", MenuConnectBtn::TimeDown);
+                    spawn_small_btn(parent, font, "Time +", MenuConnectBtn::TimeUp);
+                    spawn_small_btn(parent, font, "Geese -", MenuConnectBtn::GeeseDown);
+                    spawn_small_btn(parent, font, "Geese +", MenuConnectBtn::GeeseUp);
+                })
+                .insert(HostControls);
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..Default::default()
+                    },
+                    visibility: Visibility::Hidden,
+                    background_color: BackgroundColor(Color::NONE),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    let font = &font_assets.fira_sans;
+                    spawn_small_btn(parent, font, "Delay -", MenuConnectBtn::DelayDown);
+                    spawn_small_btn(parent, font, "Delay +", MenuConnectBtn::DelayUp);
+                    spawn_small_btn(parent, font, "Predict -", MenuConnectBtn::PredictionDown);
+                    spawn_small_btn(parent, font, "Predict +", MenuConnectBtn::PredictionUp);
+                })
+                .insert(HostControls);
+
+            // ready button
+            parent
+                .spawn(ButtonBundle {
+                    style: Style {
+                        width: Val::Px(250.),
+                        height: Val::Px(65.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::all(Val::Px(16.)),
+                        padding: UiRect::all(Val::Px(16.)),
+                        ..Default::default()
+                    },
+                    background_color: BackgroundColor(NORMAL_BUTTON),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn(TextBundle {
+                            text: Text::from_section(
+                                "Ready",
+                                TextStyle {
+                                    font: font_assets.fira_sans.clone(),
+                                    font_size: 40.0,
+                                    color: BUTTON_TEXT,
+                                },
+                            ),
+                            ..Default::default()
+                        })
+                        .insert(ReadyBtnText);
+                })
+                .insert(MenuConnectBtn::Ready)
+                .insert(ReadyControls);
+
+            // retry button
+            parent
+                .spawn(ButtonBundle {
+                    style: Style {
+                        width: Val::Px(250.),
+                        height: Val::Px(65.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::all(Val::Px(16.)),
+                        padding: UiRect::all(Val::Px(16.)),
+                        display: Display::None,
+                        ..Default::default()
+                    },
+                    background_color: BackgroundColor(NORMAL_BUTTON),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle {
+                        text: Text::from_section(
+                            "Retry",
+                            TextStyle {
+                                font: font_assets.fira_sans.clone(),
+                                font_size: 40.0,
+                                color: BUTTON_TEXT,
+                            },
+                        ),
+                        ..Default::default()
+                    });
+                })
+                .insert(MenuConnectBtn::Retry)
+                .insert(RetryControls);
+
             // back button
             parent
                 .spawn(ButtonBundle {
@@ -220,12 +871,82 @@ pub fn btn_visuals(
 }
 
 pub fn btn_listeners(
+    mut commands: Commands,
     mut state: ResMut<NextState<AppState>>,
+    mut room: ResMut<LobbyRoom>,
+    status: Res<ConnectStatus>,
+    connect_data: Res<ConnectData>,
+    profile: Res<PlayerProfile>,
     mut interaction_query: Query<(&Interaction, &MenuConnectBtn), Changed<Interaction>>,
 ) {
     for (interaction, btn) in interaction_query.iter_mut() {
         if let Interaction::Pressed = *interaction {
+            let mut settings: MatchSettings = room.settings.clone();
             match btn {
+                MenuConnectBtn::Ready => {
+                    let ready = !room.local_ready;
+                    room.set_ready(ready);
+                }
+                MenuConnectBtn::Mode => {
+                    settings.mode = settings.mode.next();
+                    room.set_settings(settings);
+                }
+                MenuConnectBtn::Map => {
+                    settings.map = (settings.map + 1) % MAPS.len();
+                    room.set_settings(settings);
+                }
+                MenuConnectBtn::LivesUp => {
+                    settings.lives = (settings.lives + 1).min(PLAYER_LIVES_MAX);
+                    room.set_settings(settings);
+                }
+                MenuConnectBtn::LivesDown => {
+                    settings.lives = (settings.lives - 1).max(PLAYER_LIVES_MIN);
+                    room.set_settings(settings);
+                }
+                MenuConnectBtn::TimeUp => {
+                    settings.time_limit =
+                        (settings.time_limit + ROUND_TIME_LIMIT_STEP).min(ROUND_TIME_LIMIT_MAX);
+                    room.set_settings(settings);
+                }
+                MenuConnectBtn::TimeDown => {
+                    settings.time_limit = settings
+                        .time_limit
+                        .saturating_sub(ROUND_TIME_LIMIT_STEP)
+                        .max(ROUND_TIME_LIMIT_MIN);
+                    room.set_settings(settings);
+                }
+                MenuConnectBtn::GeeseUp => {
+                    settings.geese_max = (settings.geese_max + 1).min(GOOSE_MAX_MAX);
+                    room.set_settings(settings);
+                }
+                MenuConnectBtn::GeeseDown => {
+                    settings.geese_max = settings.geese_max.saturating_sub(1);
+                    room.set_settings(settings);
+                }
+                MenuConnectBtn::DelayUp => {
+                    settings.netcode.input_delay = settings.netcode.input_delay.next();
+                    room.set_settings(settings);
+                }
+                MenuConnectBtn::DelayDown => {
+                    settings.netcode.input_delay = settings.netcode.input_delay.prev();
+                    room.set_settings(settings);
+                }
+                MenuConnectBtn::PredictionUp => {
+                    settings.netcode.max_prediction =
+                        (settings.netcode.max_prediction + 1).min(MAX_PREDICTION_MAX);
+                    room.set_settings(settings);
+                }
+                MenuConnectBtn::PredictionDown => {
+                    settings.netcode.max_prediction = settings
+                        .netcode
+                        .max_prediction
+                        .saturating_sub(1)
+                        .max(MAX_PREDICTION_MIN);
+                    room.set_settings(settings);
+                }
+                MenuConnectBtn::Retry => {
+                    open_socket(&mut commands, &connect_data, &profile, status.attempt + 1);
+                }
                 MenuConnectBtn::Back => {
                     state.set(AppState::MenuMain);
                 }
@@ -0,0 +1,234 @@
+use super::plugin::VERSION;
+use crate::map::map::map_hash;
+use crate::player::edibles::EdibleRegistry;
+use crate::player::input::INPUT_FORMAT_VERSION;
+use serde::{Deserialize, Serialize};
+
+/// Version of the lobby and GGRS protocol, bump on any change that breaks playing together
+pub const PROTOCOL_VERSION: u32 = 11;
+/// Oldest protocol this build can still play with
+pub const MIN_COMPATIBLE_PROTOCOL: u32 = 11;
+
+/// Rules compiled into this build that change the simulation, peers need the same set.
+/// Add an entry when a rule is added or changes behaviour.
+pub const SIMULATION_RULES: &[&str] = &[
+    "elimination",
+    "deathmatch",
+    "disconnect-elimination",
+    "chat-blocks-input",
+    "data-driven-edibles",
+    "status-effects",
+    "power-ups",
+    "shell-bash",
+    "fixed-point-physics",
+    "projectile-definitions",
+    "poop-traps",
+    "goose-behaviour",
+    "goose-population",
+    "goose-health",
+];
+
+/// Stable FNV-1a hash for everything peers compare in the handshake. Unlike `std`'s
+/// hashers it gives the same result on every build and platform.
+pub fn stable_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Range of protocol versions we accept from other peers, set in the player profile
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct CompatibilityRange {
+    pub min_protocol: u32,
+    pub max_protocol: u32,
+}
+
+impl Default for CompatibilityRange {
+    fn default() -> Self {
+        CompatibilityRange {
+            min_protocol: MIN_COMPATIBLE_PROTOCOL,
+            max_protocol: PROTOCOL_VERSION,
+        }
+    }
+}
+
+impl CompatibilityRange {
+    /// A configured range can only narrow what this build supports, it can't make
+    /// an older simulation play along. An empty range falls back to the default.
+    pub fn clamped(self) -> CompatibilityRange {
+        let range = CompatibilityRange {
+            min_protocol: self.min_protocol.max(MIN_COMPATIBLE_PROTOCOL),
+            max_protocol: self.max_protocol.min(PROTOCOL_VERSION),
+        };
+        if range.min_protocol > range.max_protocol {
+            return CompatibilityRange::default();
+        }
+        range
+    }
+
+    pub fn contains(&self, protocol: u32) -> bool {
+        (self.min_protocol..=self.max_protocol).contains(&protocol)
+    }
+}
+
+/// First message on the lobby channel. Fields default when missing so older and newer
+/// builds can still parse each other's handshake and report why they can't play.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct Handshake {
+    pub game_version: String,
+    pub protocol: u32,
+    pub min_protocol: u32,
+    pub max_protocol: u32,
+    pub input_format: u32,
+    pub map_hash: u64,
+    /// Hash of the edible definitions, their order and stats are part of the simulation
+    pub edibles_hash: u64,
+    pub rules: Vec<String>,
+}
+
+impl Handshake {
+    pub fn local(range: &CompatibilityRange, edibles: &EdibleRegistry) -> Handshake {
+        let range = range.clamped();
+        Handshake {
+            game_version: VERSION.to_owned(),
+            protocol: PROTOCOL_VERSION,
+            min_protocol: range.min_protocol,
+            max_protocol: range.max_protocol,
+            input_format: INPUT_FORMAT_VERSION,
+            map_hash: map_hash(),
+            edibles_hash: edibles.hash,
+            rules: SIMULATION_RULES.iter().map(|r| r.to_string()).collect(),
+        }
+    }
+
+    /// Checks a remote handshake against ours, the error describes the peer's side
+    /// and reads after their name, e.g. "Bob is on 0.13.0"
+    pub fn check(&self, remote: &Handshake) -> Result<(), String> {
+        let we_accept = self.range().contains(remote.protocol);
+        let they_accept = remote.range().contains(self.protocol);
+        if !we_accept || !they_accept {
+            return Err(format!(
+                "is on {} (protocol {}), we are on {} and support protocols {}-{}",
+                remote.game_version,
+                remote.protocol,
+                self.game_version,
+                self.min_protocol,
+                self.max_protocol
+            ));
+        }
+        if remote.input_format != self.input_format {
+            return Err(format!(
+                "is on {} with input format {}, we use input format {}",
+                remote.game_version, remote.input_format, self.input_format
+            ));
+        }
+        if remote.map_hash != self.map_hash {
+            return Err(format!(
+                "is on {} with a different map",
+                remote.game_version
+            ));
+        }
+        if remote.edibles_hash != self.edibles_hash {
+            return Err(format!(
+                "is on {} with different edibles",
+                remote.game_version
+            ));
+        }
+        let missing = self
+            .rules
+            .iter()
+            .filter(|r| !remote.rules.contains(r))
+            .map(|r| format!("-{}", r));
+        let extra = remote
+            .rules
+            .iter()
+            .filter(|r| !self.rules.contains(r))
+            .map(|r| format!("+{}", r));
+        let diff = missing.chain(extra).collect::<Vec<_>>();
+        if !diff.is_empty() {
+            return Err(format!(
+                "is on {} with different rules ({})",
+                remote.game_version,
+                diff.join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    fn range(&self) -> CompatibilityRange {
+        CompatibilityRange {
+            min_protocol: self.min_protocol,
+            max_protocol: self.max_protocol,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handshake(protocol: u32, min_protocol: u32, max_protocol: u32) -> Handshake {
+        Handshake {
+            protocol,
+            min_protocol,
+            max_protocol,
+            ..Handshake::local(&CompatibilityRange::default(), &EdibleRegistry::default())
+        }
+    }
+
+    #[test]
+    fn same_build_is_compatible() {
+        let local = Handshake::local(&CompatibilityRange::default(), &EdibleRegistry::default());
+        assert_eq!(local.check(&local.clone()), Ok(()));
+    }
+
+    #[test]
+    fn protocol_has_to_be_in_both_ranges() {
+        let local = handshake(5, 4, 5);
+        assert!(local.check(&handshake(4, 4, 5)).is_ok());
+        assert!(local.check(&handshake(5, 5, 5)).is_ok());
+        // we accept them, but they don't accept us
+        assert!(local.check(&handshake(4, 3, 4)).is_err());
+        // they accept us, but we don't accept them
+        assert!(local.check(&handshake(3, 3, 5)).is_err());
+        assert!(local.check(&handshake(6, 5, 6)).is_err());
+    }
+
+    #[test]
+    fn configured_range_only_narrows_the_build_range() {
+        let range = CompatibilityRange {
+            min_protocol: 0,
+            max_protocol: PROTOCOL_VERSION + 10,
+        };
+        assert_eq!(range.clamped(), CompatibilityRange::default());
+
+        let range = CompatibilityRange {
+            min_protocol: PROTOCOL_VERSION,
+            max_protocol: PROTOCOL_VERSION,
+        };
+        assert_eq!(range.clamped(), range);
+
+        let empty = CompatibilityRange {
+            min_protocol: PROTOCOL_VERSION + 1,
+            max_protocol: PROTOCOL_VERSION + 2,
+        };
+        assert_eq!(empty.clamped(), CompatibilityRange::default());
+    }
+
+    #[test]
+    fn different_rules_are_reported() {
+        let local = Handshake::local(&CompatibilityRange::default(), &EdibleRegistry::default());
+        let mut remote = local.clone();
+        remote.rules.push("moon-gravity".to_owned());
+        let err = local.check(&remote).unwrap_err();
+        assert!(err.contains("+moon-gravity"), "{}", err);
+    }
+
+    #[test]
+    fn stable_hash_is_fnv1a() {
+        assert_eq!(stable_hash(b""), 0xcbf29ce484222325);
+        assert_eq!(stable_hash(b"a"), 0xaf63dc4c8601ec8c);
+    }
+}
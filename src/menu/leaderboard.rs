@@ -0,0 +1,208 @@
+use super::plugin::{BUTTON_TEXT, HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON};
+use crate::loading::FontAssets;
+use crate::stats::profile::PlayerProfile;
+use crate::{AppState, TILE_SIZE};
+use bevy::prelude::*;
+
+#[derive(Component)]
+pub struct MenuLeaderboardUI;
+
+#[derive(Component)]
+pub enum MenuLeaderboardBtn {
+    Back,
+}
+
+fn profile_rows(profile: &PlayerProfile) -> Vec<(String, String)> {
+    let mut rows = vec![
+        (
+            "Rounds played".to_owned(),
+            profile.rounds_played.to_string(),
+        ),
+        ("Wins".to_owned(), profile.wins.to_string()),
+        ("Losses".to_owned(), profile.losses.to_string()),
+        ("Win rate".to_owned(), format!("{:.0}%", profile.win_rate())),
+        ("Kills".to_owned(), profile.kills.to_string()),
+        ("Deaths".to_owned(), profile.deaths.to_string()),
+        (
+            "Most kills in a round".to_owned(),
+            profile.most_kills_in_round.to_string(),
+        ),
+        (
+            "Fireballs hit".to_owned(),
+            format!(
+                "{} / {} ({:.0}%)",
+                profile.fireballs_hit,
+                profile.fireballs_fired,
+                profile.accuracy()
+            ),
+        ),
+        (
+            "Poop damage dealt".to_owned(),
+            profile.poop_damage_dealt.to_string(),
+        ),
+        (
+            "Geese defeated".to_owned(),
+            profile.geese_killed.to_string(),
+        ),
+        (
+            "Distance moved".to_owned(),
+            format!("{:.0} tiles", profile.distance_moved / TILE_SIZE),
+        ),
+    ];
+    for (edible, count) in profile.edibles_eaten.iter() {
+        rows.push((format!("{} eaten", edible), count.to_string()));
+    }
+    rows
+}
+
+pub fn setup_ui(mut commands: Commands, font_assets: Res<FontAssets>, profile: Res<PlayerProfile>) {
+    // ui camera
+    commands
+        .spawn(Camera2dBundle::default())
+        .insert(MenuLeaderboardUI);
+
+    // root node
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(0.),
+                right: Val::Px(0.),
+                top: Val::Px(0.),
+                bottom: Val::Px(0.),
+                flex_direction: FlexDirection::Column,
+                align_content: AlignContent::Center,
+                align_items: AlignItems::Center,
+                align_self: AlignSelf::Center,
+                justify_content: JustifyContent::Center,
+                ..Default::default()
+            },
+            background_color: BackgroundColor(Color::NONE),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent.spawn(TextBundle {
+                text: Text::from_section(
+                    "Leaderboard",
+                    TextStyle {
+                        font: font_assets.fira_sans.clone(),
+                        font_size: 50.0,
+                        color: BUTTON_TEXT,
+                    },
+                ),
+                ..Default::default()
+            });
+
+            // stat table, one row per stat
+            for (label, value) in profile_rows(&profile) {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(600.),
+                            flex_direction: FlexDirection::Row,
+                            justify_content: JustifyContent::SpaceBetween,
+                            ..Default::default()
+                        },
+                        background_color: BackgroundColor(Color::NONE),
+                        ..Default::default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle {
+                            text: Text::from_section(
+                                label,
+                                TextStyle {
+                                    font: font_assets.fira_sans.clone(),
+                                    font_size: 30.0,
+                                    color: BUTTON_TEXT,
+                                },
+                            ),
+                            ..Default::default()
+                        });
+                        parent.spawn(TextBundle {
+                            text: Text::from_section(
+                                value,
+                                TextStyle {
+                                    font: font_assets.fira_sans.clone(),
+                                    font_size: 30.0,
+                                    color: Color::GOLD,
+                                },
+                            ),
+                            ..Default::default()
+                        });
+                    });
+            }
+
+            // back button
+            parent
+                .spawn(ButtonBundle {
+                    style: Style {
+                        width: Val::Px(250.0),
+                        height: Val::Px(65.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::all(Val::Px(16.)),
+                        padding: UiRect::all(Val::Px(16.)),
+                        ..Default::default()
+                    },
+                    background_color: BackgroundColor(NORMAL_BUTTON),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle {
+                        text: Text::from_section(
+                            "Back to Menu",
+                            TextStyle {
+                                font: font_assets.fira_sans.clone(),
+                                font_size: 40.0,
+                                color: BUTTON_TEXT,
+                            },
+                        ),
+                        ..Default::default()
+                    });
+                })
+                .insert(MenuLeaderboardBtn::Back);
+        })
+        .insert(MenuLeaderboardUI);
+}
+
+pub fn btn_listeners(
+    mut state: ResMut<NextState<AppState>>,
+    mut interaction_query: Query<(&Interaction, &MenuLeaderboardBtn), Changed<Interaction>>,
+) {
+    for (interaction, btn) in interaction_query.iter_mut() {
+        if let Interaction::Pressed = *interaction {
+            match btn {
+                MenuLeaderboardBtn::Back => {
+                    state.set(AppState::MenuMain);
+                }
+            }
+        }
+    }
+}
+
+pub fn btn_visuals(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<MenuLeaderboardBtn>),
+    >,
+) {
+    for (interaction, mut color) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = PRESSED_BUTTON.into();
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+pub fn cleanup_ui(query: Query<Entity, With<MenuLeaderboardUI>>, mut commands: Commands) {
+    for e in query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
@@ -0,0 +1,557 @@
+use super::connect::{ConnectError, ConnectStatus};
+use super::handshake::Handshake;
+use super::online::sanitize_player_name;
+use crate::player::edibles::EdibleRegistry;
+use crate::player::resources::{InputDelay, InputHistory, MatchSettings, NetcodeSettings};
+use crate::stats::profile::PlayerProfile;
+use crate::AppState;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_matchbox::matchbox_socket::{Packet, WebRtcChannel};
+use bevy_matchbox::prelude::{MultipleChannels, PeerId, PeerState};
+use bevy_matchbox::MatchboxSocket;
+use ggrs::{Message, NonBlockingSocket};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+/// How often every peer measures its round trip time to the others
+pub const LOBBY_PING_INTERVAL: f64 = 1.0;
+/// Weight of a new round trip sample in the smoothed ping
+const PING_SMOOTHING: f64 = 0.3;
+/// Frames of confirmed inputs sent to a spectator per message
+const SPECTATOR_CHUNK_FRAMES: usize = 120;
+/// Messages sent to every spectator per update, so catching up doesn't flood the channel
+const SPECTATOR_CHUNKS_PER_UPDATE: usize = 8;
+
+/// Socket channel handed over to GGRS once the session starts
+pub const GGRS_CHANNEL: usize = 0;
+/// Reliable side channel used for lobby messages like names, ready state and settings
+pub const LOBBY_CHANNEL: usize = 1;
+/// Reliable side channel used for text chat
+pub const CHAT_CHANNEL: usize = 2;
+
+/// Messages exchanged between peers on the lobby channel
+#[derive(Debug, Deserialize, Serialize)]
+pub enum LobbyMessage {
+    /// Always sent first, tells peers whether we can play together at all
+    Hello(Handshake),
+    /// Seconds since we joined the room, decides who gets a seat when too many join
+    Joined(f64),
+    Name(String),
+    Ready(bool),
+    Settings(MatchSettings),
+    /// Round trip measurement, the receiver echoes the sender's timestamp back
+    Ping(f64),
+    Pong(f64),
+    /// Vote to play another round with the same group, tagged with the round it is for
+    Rematch(u32),
+    /// Sent to peers joining while a round is running, they spectate until the next one
+    RoundInProgress(RoundInProgress),
+    /// Confirmed inputs of the running round starting at the given frame
+    Inputs(u32, Vec<Vec<(u16, bool)>>),
+    /// The players left the round, there are no more inputs to come
+    RoundOver,
+}
+
+/// Everything a late joiner needs to replay the running round from its inputs
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RoundInProgress {
+    /// Round number the agreed random was seeded with
+    pub round: u32,
+    /// Players ordered by handle
+    pub players: Vec<PeerId>,
+    pub names: Vec<String>,
+    pub settings: MatchSettings,
+}
+
+impl LobbyMessage {
+    pub fn to_packet(&self) -> Option<Packet> {
+        match ron::to_string(self) {
+            Ok(data) => Some(data.into_bytes().into_boxed_slice()),
+            Err(e) => {
+                warn!("unable to serialize lobby message: {:?}", e);
+                None
+            }
+        }
+    }
+
+    pub fn from_packet(packet: &Packet) -> Option<LobbyMessage> {
+        let data = std::str::from_utf8(packet).ok()?;
+        match ron::from_str(data) {
+            Ok(msg) => Some(msg),
+            Err(e) => {
+                warn!("unable to parse lobby message: {:?}", e);
+                None
+            }
+        }
+    }
+}
+
+/// Sends a lobby message to a single peer over the reliable lobby channel
+pub fn send_lobby_message(
+    socket: &mut MatchboxSocket<MultipleChannels>,
+    peer: PeerId,
+    msg: &LobbyMessage,
+) {
+    if let Some(packet) = msg.to_packet() {
+        // fails once the socket closed, the connect menu reports that separately
+        if let Err(e) = socket.channel(LOBBY_CHANNEL).try_send(packet, peer) {
+            warn!("unable to send lobby message to {peer:?}: {e:?}");
+        }
+    }
+}
+
+pub fn local_name(profile: &PlayerProfile) -> String {
+    sanitize_player_name(&profile.name)
+}
+
+/// The GGRS channel can only be taken from the socket once. Sharing it lets every
+/// rematch start a new session on the same connection instead of reconnecting.
+#[derive(Clone)]
+pub struct GgrsChannel(Arc<Mutex<WebRtcChannel>>);
+
+impl GgrsChannel {
+    pub fn new(channel: WebRtcChannel) -> GgrsChannel {
+        GgrsChannel(Arc::new(Mutex::new(channel)))
+    }
+
+    /// Drops packets still in flight from the previous session
+    pub fn drain(&self) {
+        let dropped = self.0.lock().unwrap().receive().len();
+        debug!("dropped {dropped} stale GGRS packets");
+    }
+}
+
+impl NonBlockingSocket<PeerId> for GgrsChannel {
+    fn send_to(&mut self, msg: &Message, addr: &PeerId) {
+        self.0.lock().unwrap().send_to(msg, addr);
+    }
+
+    fn receive_all_messages(&mut self) -> Vec<(PeerId, Message)> {
+        self.0.lock().unwrap().receive_all_messages()
+    }
+}
+
+/// Everything we know about the pre-game lobby: who joined, who is ready and
+/// which settings the host picked. The host is the peer with the lowest PeerId.
+#[derive(Default, Resource)]
+pub struct LobbyRoom {
+    pub local_id: Option<PeerId>,
+    pub peers: Vec<PeerId>,
+    pub names: HashMap<PeerId, String>,
+    /// Result of checking every peer's handshake against ours
+    pub handshakes: HashMap<PeerId, Result<(), String>>,
+    /// Estimated local time every peer joined the room
+    pub joined_at: HashMap<PeerId, f64>,
+    pub local_joined_at: Option<f64>,
+    pub ready: HashMap<PeerId, bool>,
+    pub local_ready: bool,
+    pub settings: MatchSettings,
+    pub settings_received: bool,
+    /// Smoothed round trip time to every peer in milliseconds
+    pub pings: HashMap<PeerId, f64>,
+    /// Local time we last got any lobby message from every peer, pings included
+    pub last_heard: HashMap<PeerId, f64>,
+    last_ping: f64,
+    /// Number of sessions this group has started, seeds the agreed random for each round
+    pub round: u32,
+    /// The round every peer voted to play next
+    pub rematch_votes: HashMap<PeerId, u32>,
+    pub local_rematch: Option<u32>,
+    pub ggrs_channel: Option<GgrsChannel>,
+    /// The round being played or last played, a spectated one if we are not among its players
+    pub current_round: Option<RoundInProgress>,
+    /// How far we streamed the current round to every spectating peer
+    spectators: HashMap<PeerId, SpectatorFeed>,
+    outbox: Vec<LobbyMessage>,
+}
+
+#[derive(Default)]
+struct SpectatorFeed {
+    frames_sent: usize,
+    round_over_sent: bool,
+}
+
+impl LobbyRoom {
+    pub fn new(settings: MatchSettings) -> LobbyRoom {
+        LobbyRoom {
+            settings,
+            ..Default::default()
+        }
+    }
+
+    pub fn host(&self) -> Option<PeerId> {
+        self.peers.iter().chain(self.local_id.iter()).min().copied()
+    }
+
+    pub fn is_host(&self) -> bool {
+        self.local_id.is_some() && self.host() == self.local_id
+    }
+
+    /// Settings are agreed once we are the host or heard from the host
+    pub fn settings_agreed(&self) -> bool {
+        self.is_host() || self.settings_received
+    }
+
+    pub fn everyone_greeted(&self) -> bool {
+        self.peers
+            .iter()
+            .all(|p| self.handshakes.contains_key(p) && self.joined_at.contains_key(p))
+    }
+
+    /// Whether we are among the first `seats` players to join the room.
+    /// Peers that joined at nearly the same moment may disagree, ties go to the lower id.
+    pub fn seated(&self, seats: usize) -> bool {
+        let (local_id, local_joined_at) = match (self.local_id, self.local_joined_at) {
+            (Some(id), Some(at)) => (id, at),
+            _ => return true,
+        };
+        let mut order = self
+            .peers
+            .iter()
+            .filter_map(|p| self.joined_at.get(p).map(|at| (*at, *p)))
+            .chain(std::iter::once((local_joined_at, local_id)))
+            .collect::<Vec<_>>();
+        order.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        order.iter().take(seats).any(|(_, id)| *id == local_id)
+    }
+
+    /// First peer we can't play with and why
+    pub fn incompatible_peer(&self) -> Option<(PeerId, &String)> {
+        self.peers
+            .iter()
+            .find_map(|p| match self.handshakes.get(p) {
+                Some(Err(reason)) => Some((*p, reason)),
+                _ => None,
+            })
+    }
+
+    pub fn everyone_named(&self) -> bool {
+        self.peers.iter().all(|p| self.names.contains_key(p))
+    }
+
+    pub fn everyone_ready(&self) -> bool {
+        self.local_ready && self.peers.iter().all(|p| self.ready.get(p) == Some(&true))
+    }
+
+    pub fn set_ready(&mut self, ready: bool) {
+        self.local_ready = ready;
+        self.outbox.push(LobbyMessage::Ready(ready));
+    }
+
+    /// Only the host may change settings, everyone has to ready up again afterwards
+    pub fn set_settings(&mut self, settings: MatchSettings) {
+        let settings = settings.clamped();
+        if !self.is_host() || self.settings == settings {
+            return;
+        }
+        self.settings = settings.clone();
+        self.set_ready(false);
+        self.outbox.push(LobbyMessage::Settings(settings));
+    }
+
+    pub fn vote_rematch(&mut self) {
+        self.local_rematch = Some(self.round);
+        self.outbox.push(LobbyMessage::Rematch(self.round));
+    }
+
+    pub fn voted_rematch(&self) -> bool {
+        self.local_rematch == Some(self.round)
+    }
+
+    /// Number of players, including us, who want to play the next round
+    pub fn rematch_count(&self) -> usize {
+        let remote = self
+            .peers
+            .iter()
+            .filter(|p| self.rematch_votes.get(*p) == Some(&self.round))
+            .count();
+        remote + self.voted_rematch() as usize
+    }
+
+    /// Whether we play in the current round rather than spectate it
+    pub fn playing_current_round(&self) -> bool {
+        match (&self.current_round, self.local_id) {
+            (Some(round), Some(local_id)) => round.players.contains(&local_id),
+            _ => false,
+        }
+    }
+
+    /// A new session started, everyone in it plays and nobody needs our inputs yet
+    pub fn start_round(&mut self, round: RoundInProgress) {
+        self.current_round = Some(round);
+        self.spectators.clear();
+    }
+
+    pub fn everyone_wants_rematch(&self) -> bool {
+        !self.peers.is_empty() && self.rematch_count() == self.peers.len() + 1
+    }
+
+    /// First peer we haven't heard from for `timeout` seconds, they send pings every second
+    pub fn silent_peer(&self, now: f64, timeout: f64) -> Option<PeerId> {
+        self.peers
+            .iter()
+            .find(|p| self.last_heard.get(*p).is_some_and(|at| now - at > timeout))
+            .copied()
+    }
+
+    /// Display name of a peer, for messages about them
+    pub fn peer_name(&self, peer: PeerId) -> String {
+        self.names
+            .get(&peer)
+            .filter(|n| !n.is_empty())
+            .cloned()
+            .unwrap_or_else(|| "A peer".to_owned())
+    }
+
+    fn record_ping(&mut self, peer: PeerId, rtt_ms: f64) {
+        let smoothed = match self.pings.get(&peer) {
+            Some(old) => old + (rtt_ms - old) * PING_SMOOTHING,
+            None => rtt_ms,
+        };
+        self.pings.insert(peer, smoothed);
+    }
+
+    /// The host keeps the auto input delay in line with the slowest peer until it readies up,
+    /// after that the settings stay frozen so nobody gets unreadied by ping jitter
+    fn update_auto_input_delay(&mut self) {
+        if !self.is_host()
+            || self.local_ready
+            || self.settings.netcode.input_delay != InputDelay::Auto
+        {
+            return;
+        }
+        if self.peers.is_empty() || !self.peers.iter().all(|p| self.pings.contains_key(p)) {
+            return;
+        }
+        let max_rtt = self.pings.values().copied().fold(0., f64::max);
+        let mut settings = self.settings.clone();
+        settings.netcode.auto_input_delay = NetcodeSettings::input_delay_for_ping(max_rtt);
+        self.set_settings(settings);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn lobby_network_system(
+    time: Res<Time<Real>>,
+    mut socket: ResMut<MatchboxSocket<MultipleChannels>>,
+    mut room: ResMut<LobbyRoom>,
+    profile: Res<PlayerProfile>,
+    mut status: ResMut<ConnectStatus>,
+    mut history: ResMut<InputHistory>,
+    edibles: Res<EdibleRegistry>,
+) {
+    if !edibles.is_loaded() {
+        status.fail(ConnectError::EdiblesMissing);
+        return;
+    }
+    let handshake = Handshake::local(&profile.compatibility, &edibles);
+    let now = time.elapsed_seconds_f64();
+    room.local_id = socket.id();
+    if room.local_id.is_some() && room.local_joined_at.is_none() {
+        room.local_joined_at = Some(now);
+        status.progress(now);
+    }
+
+    // regularly update the list of connected peers, this fails once the socket closed
+    let changes = match socket.try_update_peers() {
+        Ok(changes) => changes,
+        Err(_) => {
+            status.socket_closed();
+            return;
+        }
+    };
+    for (peer, new_state) in changes {
+        status.progress(now);
+        match new_state {
+            PeerState::Connected => {
+                info!("peer {peer:?} connected");
+                room.peers.push(peer);
+                room.peers.sort();
+                room.last_heard.insert(peer, now);
+                send_lobby_message(&mut socket, peer, &LobbyMessage::Hello(handshake.clone()));
+                let in_room = now - room.local_joined_at.unwrap_or(now);
+                send_lobby_message(&mut socket, peer, &LobbyMessage::Joined(in_room));
+                send_lobby_message(&mut socket, peer, &LobbyMessage::Name(local_name(&profile)));
+                if room.is_host() {
+                    let msg = LobbyMessage::Settings(room.settings.clone());
+                    send_lobby_message(&mut socket, peer, &msg);
+                }
+            }
+            PeerState::Disconnected => {
+                info!("peer {peer:?} disconnected");
+                if !room.peers.contains(&peer) {
+                    // they left the signaling server before we ever got a WebRTC connection
+                    warn!("peer {peer:?} left before a connection was established");
+                    status.failed_peers += 1;
+                }
+                room.peers.retain(|p| *p != peer);
+                room.names.remove(&peer);
+                room.handshakes.remove(&peer);
+                room.joined_at.remove(&peer);
+                room.ready.remove(&peer);
+                room.pings.remove(&peer);
+                room.last_heard.remove(&peer);
+                room.rematch_votes.remove(&peer);
+                room.spectators.remove(&peer);
+                if room.is_host() {
+                    // we may have just taken over as host, make sure everyone has our settings
+                    let settings = room.settings.clone();
+                    room.outbox.push(LobbyMessage::Settings(settings));
+                }
+            }
+        }
+        // the roster changed, everyone has to confirm again
+        room.set_ready(false);
+    }
+
+    for (peer, packet) in socket.channel(LOBBY_CHANNEL).receive() {
+        room.last_heard.insert(peer, now);
+        let msg = LobbyMessage::from_packet(&packet);
+        if !matches!(msg, Some(LobbyMessage::Ping(_) | LobbyMessage::Pong(_))) {
+            // pings only show the peer is there, not that the lobby moves on
+            status.progress(now);
+        }
+        match msg {
+            Some(LobbyMessage::Hello(remote)) => {
+                let result = handshake.check(&remote);
+                match &result {
+                    Ok(_) => debug!("peer {peer:?} is compatible: {remote:?}"),
+                    Err(reason) => warn!("peer {peer:?} {reason}"),
+                }
+                room.handshakes.insert(peer, result);
+            }
+            Some(LobbyMessage::Joined(in_room)) => {
+                room.joined_at.insert(peer, now - in_room.max(0.));
+            }
+            Some(LobbyMessage::Name(name)) => {
+                let name = sanitize_player_name(&name);
+                debug!("peer {peer:?} is named {name:?}");
+                room.names.insert(peer, name);
+            }
+            Some(LobbyMessage::Ready(ready)) => {
+                debug!("peer {peer:?} ready: {ready}");
+                room.ready.insert(peer, ready);
+            }
+            Some(LobbyMessage::Settings(settings)) => {
+                if room.host() != Some(peer) {
+                    warn!("ignoring settings from {peer:?}, they are not the host");
+                    continue;
+                }
+                let settings = settings.clamped();
+                debug!("host {peer:?} picked settings {settings:?}");
+                if room.settings != settings {
+                    room.set_ready(false);
+                }
+                room.settings = settings;
+                room.settings_received = true;
+            }
+            Some(LobbyMessage::Ping(sent)) => {
+                send_lobby_message(&mut socket, peer, &LobbyMessage::Pong(sent));
+            }
+            Some(LobbyMessage::Pong(sent)) => {
+                let rtt_ms = (now - sent) * 1000.;
+                if rtt_ms >= 0. {
+                    room.record_ping(peer, rtt_ms);
+                }
+            }
+            Some(LobbyMessage::Rematch(round)) => {
+                debug!("peer {peer:?} wants to play round {round}");
+                room.rematch_votes.insert(peer, round);
+            }
+            Some(LobbyMessage::RoundInProgress(round)) => {
+                // every player takes over streaming when the previous one leaves, so this repeats
+                if room.playing_current_round() || room.current_round.as_ref() == Some(&round) {
+                    continue;
+                }
+                info!(
+                    "joined during round {}, spectating until the next one",
+                    round.round
+                );
+                // votes for the next round have to match the players' round count
+                room.round = round.round + 1;
+                room.current_round = Some(round);
+                *history = InputHistory::default();
+            }
+            Some(LobbyMessage::Inputs(start, frames)) => {
+                if room.current_round.is_some() && !room.playing_current_round() {
+                    history.extend_from(start as usize, frames);
+                }
+            }
+            Some(LobbyMessage::RoundOver) => {
+                if room.current_round.is_some() && !room.playing_current_round() {
+                    history.complete = true;
+                }
+            }
+            None => warn!("dropping invalid lobby message from {peer:?}"),
+        }
+    }
+
+    if now - room.last_ping > LOBBY_PING_INTERVAL {
+        room.last_ping = now;
+        room.outbox.push(LobbyMessage::Ping(now));
+    }
+    room.update_auto_input_delay();
+
+    // broadcast everything queued up by the lobby UI
+    let outbox = std::mem::take(&mut room.outbox);
+    for msg in outbox.iter() {
+        for peer in room.peers.iter() {
+            send_lobby_message(&mut socket, *peer, msg);
+        }
+    }
+}
+
+/// Streams the confirmed inputs of the current round to peers that joined after it started.
+/// Only the connected player with the lowest id streams, the others take over if they leave.
+pub fn stream_round_to_spectators(
+    state: Res<State<AppState>>,
+    mut socket: ResMut<MatchboxSocket<MultipleChannels>>,
+    mut room: ResMut<LobbyRoom>,
+    history: Res<InputHistory>,
+) {
+    let (Some(local_id), Some(round)) = (room.local_id, room.current_round.clone()) else {
+        return;
+    };
+    let streamer = round
+        .players
+        .iter()
+        .filter(|p| **p == local_id || room.peers.contains(p))
+        .min();
+    if streamer != Some(&local_id) {
+        return;
+    }
+    let round_over = *state.get() != AppState::RoundOnline;
+
+    let spectators = room
+        .peers
+        .iter()
+        .filter(|p| !round.players.contains(p) && room.handshakes.get(*p) == Some(&Ok(())))
+        .copied()
+        .collect::<Vec<_>>();
+    for peer in spectators {
+        let mut feed = match room.spectators.remove(&peer) {
+            Some(feed) => feed,
+            None => {
+                let msg = LobbyMessage::RoundInProgress(round.clone());
+                send_lobby_message(&mut socket, peer, &msg);
+                SpectatorFeed::default()
+            }
+        };
+        for _ in 0..SPECTATOR_CHUNKS_PER_UPDATE {
+            if feed.frames_sent >= history.frames.len() {
+                break;
+            }
+            let end = (feed.frames_sent + SPECTATOR_CHUNK_FRAMES).min(history.frames.len());
+            let frames = history.frames[feed.frames_sent..end].to_vec();
+            let msg = LobbyMessage::Inputs(feed.frames_sent as u32, frames);
+            send_lobby_message(&mut socket, peer, &msg);
+            feed.frames_sent = end;
+        }
+        if round_over && !feed.round_over_sent && feed.frames_sent == history.frames.len() {
+            send_lobby_message(&mut socket, peer, &LobbyMessage::RoundOver);
+            feed.round_over_sent = true;
+        }
+        room.spectators.insert(peer, feed);
+    }
+}
@@ -0,0 +1,132 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+pub const LOBBY_CODE_LEN: usize = 6;
+/// Characters used for generated codes, leaving out the easily confused 0/O and 1/I
+const LOBBY_CODE_CHARS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+#[cfg(target_arch = "wasm32")]
+const LOBBY_URL_PARAM: &str = "lobby";
+
+pub fn generate_lobby_code() -> String {
+    let mut rng = rand::thread_rng();
+    (0..LOBBY_CODE_LEN)
+        .map(|_| LOBBY_CODE_CHARS[rng.gen_range(0..LOBBY_CODE_CHARS.len())] as char)
+        .collect()
+}
+
+/// Typed codes are case insensitive, anything that isn't alphanumeric is rejected
+pub fn normalize_code_char(c: char) -> Option<char> {
+    if c.is_ascii_alphanumeric() {
+        Some(c.to_ascii_uppercase())
+    } else {
+        None
+    }
+}
+
+pub fn is_valid_lobby_code(code: &str) -> bool {
+    code.len() == LOBBY_CODE_LEN && code.chars().all(|c| normalize_code_char(c) == Some(c))
+}
+
+/// Text a friend needs to join, natively that's just the code
+#[cfg(not(target_arch = "wasm32"))]
+pub fn share_text(code: &str) -> String {
+    code.to_owned()
+}
+
+/// Text a friend needs to join, on the web that's a link that opens the lobby
+#[cfg(target_arch = "wasm32")]
+pub fn share_text(code: &str) -> String {
+    share_link(code).unwrap_or_else(|| code.to_owned())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn copy_to_clipboard(text: &str) -> bool {
+    match arboard::Clipboard::new().and_then(|mut c| c.set_text(text)) {
+        Ok(_) => true,
+        Err(e) => {
+            warn!("unable to copy to clipboard: {:?}", e);
+            false
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn copy_to_clipboard(text: &str) -> bool {
+    use wasm_bindgen::JsCast;
+
+    // navigator.clipboard is still behind web-sys' unstable apis, call it through reflection
+    let write = || -> Option<()> {
+        let window = web_sys::window()?;
+        let navigator = js_sys::Reflect::get(&window, &"navigator".into()).ok()?;
+        let clipboard = js_sys::Reflect::get(&navigator, &"clipboard".into()).ok()?;
+        let write_text = js_sys::Reflect::get(&clipboard, &"writeText".into())
+            .ok()?
+            .dyn_into::<js_sys::Function>()
+            .ok()?;
+        write_text.call1(&clipboard, &text.into()).ok()?;
+        Some(())
+    };
+    match write() {
+        Some(_) => true,
+        None => {
+            warn!("clipboard unavailable, lobby code not copied");
+            false
+        }
+    }
+}
+
+/// There is no page URL to read a code from outside the browser
+#[cfg(not(target_arch = "wasm32"))]
+pub fn lobby_code_from_url() -> Option<String> {
+    None
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn set_lobby_code_in_url(_code: &str) {}
+
+#[cfg(target_arch = "wasm32")]
+pub fn lobby_code_from_url() -> Option<String> {
+    let search = web_sys::window()?.location().search().ok()?;
+    let code = search
+        .trim_start_matches('?')
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == LOBBY_URL_PARAM)?
+        .1
+        .chars()
+        .filter_map(normalize_code_char)
+        .collect::<String>();
+    if is_valid_lobby_code(&code) {
+        Some(code)
+    } else {
+        warn!("ignoring invalid lobby code {:?} from the page url", code);
+        None
+    }
+}
+
+/// Puts the code in the address bar so the page URL itself can be shared
+#[cfg(target_arch = "wasm32")]
+pub fn set_lobby_code_in_url(code: &str) {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    let url = format!("?{}={}", LOBBY_URL_PARAM, code);
+    if let Ok(history) = window.history() {
+        if let Err(e) = history.replace_state_with_url(&wasm_bindgen::JsValue::NULL, "", Some(&url))
+        {
+            warn!("unable to update the page url: {:?}", e);
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn share_link(code: &str) -> Option<String> {
+    let location = web_sys::window()?.location();
+    Some(format!(
+        "{}{}?{}={}",
+        location.origin().ok()?,
+        location.pathname().ok()?,
+        LOBBY_URL_PARAM,
+        code
+    ))
+}
@@ -1,10 +1,14 @@
 use super::connect::{ConnectData, LocalHandle};
 use super::online::PlayerCount;
-use super::plugin::{BUTTON_TEXT, HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON, VERSION};
+use super::plugin::{
+    BUTTON_TEXT, DISABLED_BUTTON, HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON, VERSION,
+};
 use crate::loading::{FontAssets, TextureAssets};
+use crate::player::edibles::EdibleRegistry;
 use crate::player::input::GGRSConfig;
-use crate::player::resources::AgreedRandom;
-use crate::{AppState, GameState, CHECK_DISTANCE, FPS, INPUT_DELAY, MAX_PREDICTION};
+use crate::player::resources::{AgreedRandom, InputHistory, NetcodeSettings};
+use crate::stats::profile::PlayerProfile;
+use crate::{AppState, GameState, FPS};
 use bevy::utils::Uuid;
 use bevy::{app::AppExit, prelude::*};
 use bevy_ggrs::Session;
@@ -14,11 +18,16 @@ use ggrs::{PlayerType, SessionBuilder};
 #[derive(Component)]
 pub struct MainMenuUI;
 
+/// Shows why a local match could not be started
+#[derive(Component)]
+pub struct MainMenuErrorText;
+
 #[derive(Component)]
 pub enum MainMenuBtn {
     OnlineMatch,
     LocalMatch,
     Options,
+    Leaderboard,
     Quit,
 }
 
@@ -171,6 +180,36 @@ pub fn setup_ui(
                 })
                 .insert(MainMenuBtn::Options);
 
+            // leaderboard button
+            parent
+                .spawn(ButtonBundle {
+                    style: Style {
+                        width: Val::Px(200.0),
+                        height: Val::Px(65.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::all(Val::Px(16.)),
+                        padding: UiRect::all(Val::Px(16.)),
+                        ..Default::default()
+                    },
+                    background_color: BackgroundColor(NORMAL_BUTTON),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle {
+                        text: Text::from_section(
+                            "Leaderboard",
+                            TextStyle {
+                                font: font_assets.fira_sans.clone(),
+                                font_size: 40.0,
+                                color: BUTTON_TEXT,
+                            },
+                        ),
+                        ..Default::default()
+                    });
+                })
+                .insert(MainMenuBtn::Leaderboard);
+
             // quit button
             parent
                 .spawn(ButtonBundle {
@@ -212,6 +251,20 @@ pub fn setup_ui(
                 ),
                 ..Default::default()
             });
+
+            parent
+                .spawn(TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: font_assets.fira_sans.clone(),
+                            font_size: 28.0,
+                            color: DISABLED_BUTTON,
+                        },
+                    ),
+                    ..Default::default()
+                })
+                .insert(MainMenuErrorText);
         })
         .insert(MainMenuUI);
 }
@@ -237,13 +290,17 @@ pub fn btn_visuals(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn btn_listeners(
     mut exit: EventWriter<AppExit>,
     mut commands: Commands,
     mut app_state: ResMut<NextState<AppState>>,
     mut game_state: ResMut<NextState<GameState>>,
     player_count: Res<PlayerCount>,
+    profile: Res<PlayerProfile>,
+    edibles: Res<EdibleRegistry>,
     mut interaction_query: Query<(&Interaction, &MainMenuBtn), Changed<Interaction>>,
+    mut error_query: Query<&mut Text, With<MainMenuErrorText>>,
 ) {
     for (interaction, btn) in interaction_query.iter_mut() {
         if let Interaction::Pressed = *interaction {
@@ -255,13 +312,30 @@ pub fn btn_listeners(
                     // remove any lingering online connect data
                     commands.remove_resource::<ConnectData>();
 
-                    create_synctest_session(&mut commands, player_count.0);
-                    app_state.set(AppState::RoundLocal);
-                    game_state.set(GameState::Playing);
+                    match create_synctest_session(
+                        &mut commands,
+                        player_count.0,
+                        &profile.netcode,
+                        &edibles,
+                    ) {
+                        Ok(()) => {
+                            app_state.set(AppState::RoundLocal);
+                            game_state.set(GameState::Playing);
+                        }
+                        Err(e) => {
+                            warn!("unable to start local match: {}", e);
+                            for mut text in error_query.iter_mut() {
+                                text.sections[0].value = format!("Unable to start: {}", e);
+                            }
+                        }
+                    }
                 }
                 MainMenuBtn::Options => {
                     app_state.set(AppState::MenuOptions);
                 }
+                MainMenuBtn::Leaderboard => {
+                    app_state.set(AppState::MenuLeaderboard);
+                }
                 MainMenuBtn::Quit => {
                     exit.send(AppExit);
                 }
@@ -276,27 +350,40 @@ pub fn cleanup_ui(query: Query<Entity, With<MainMenuUI>>, mut commands: Commands
     }
 }
 
-fn create_synctest_session(commands: &mut Commands, num_players: usize) {
+fn create_synctest_session(
+    commands: &mut Commands,
+    num_players: usize,
+    netcode: &NetcodeSettings,
+    edibles: &EdibleRegistry,
+) -> Result<(), String> {
+    if !edibles.is_loaded() {
+        return Err("the edibles file is missing or invalid".to_owned());
+    }
     let mut sess_build = SessionBuilder::<GGRSConfig>::new()
         .with_num_players(num_players)
-        .with_max_prediction_window(MAX_PREDICTION)
-        .expect("Invalid MAX_PREDICTION")
+        .with_max_prediction_window(netcode.max_prediction)
+        .map_err(|e| format!("invalid max prediction: {}", e))?
         .with_fps(FPS)
-        .expect("Invalid FPS")
-        .with_input_delay(INPUT_DELAY)
-        .with_check_distance(CHECK_DISTANCE);
+        .map_err(|e| format!("invalid fps: {}", e))?
+        .with_input_delay(netcode.input_delay())
+        .with_check_distance(netcode.check_distance());
 
     let mut peers = Vec::new();
     for i in 0..num_players {
         sess_build = sess_build
             .add_player(PlayerType::Local, i)
-            .expect("Could not add local player");
+            .map_err(|e| format!("unable to add player {}: {}", i, e))?;
         peers.push(PeerId(Uuid::new_v4()))
     }
 
-    let sess = sess_build.start_synctest_session().expect("");
+    let sess = sess_build
+        .start_synctest_session()
+        .map_err(|e| format!("unable to start session: {}", e))?;
 
     commands.insert_resource(Session::SyncTest(sess));
     commands.insert_resource(LocalHandle(0));
-    commands.insert_resource(AgreedRandom::new(peers));
+    commands.insert_resource(AgreedRandom::new(peers, 0));
+    // local rounds have nobody to stream to, drop whatever the last online round recorded
+    commands.insert_resource(InputHistory::default());
+    Ok(())
 }
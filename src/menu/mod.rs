@@ -1,4 +1,8 @@
 pub mod connect;
+pub mod handshake;
+pub mod leaderboard;
+pub mod lobby;
+pub mod lobby_code;
 pub mod main;
 pub mod online;
 pub mod options;
@@ -1,21 +1,44 @@
 use super::connect::ConnectData;
-use super::plugin::{
-    BUTTON_TEXT, DISABLED_BUTTON, HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON, VERSION,
+use super::handshake::PROTOCOL_VERSION;
+use super::lobby_code::{
+    copy_to_clipboard, generate_lobby_code, is_valid_lobby_code, lobby_code_from_url,
+    normalize_code_char, set_lobby_code_in_url, share_text, LOBBY_CODE_LEN,
 };
+use super::plugin::{BUTTON_TEXT, DISABLED_BUTTON, HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON};
 use crate::loading::FontAssets;
+use crate::stats::profile::PlayerProfile;
 use crate::AppState;
 use bevy::prelude::*;
 
 const MIN_PLAYERS: usize = 2;
-const MAX_PLAYERS: usize = 8;
+pub const MAX_PLAYERS: usize = 8;
+pub const PLAYER_NAME_MAX_LEN: usize = 12;
+
+/// Characters allowed in a player name
+pub fn is_valid_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == ' ' || c == '_'
+}
+
+/// Cleans up a name typed locally or received from a peer. Counts characters rather than bytes,
+/// so a multibyte name from a peer can't be cut in the middle of a character.
+pub fn sanitize_player_name(name: &str) -> String {
+    name.trim()
+        .chars()
+        .filter(|c| is_valid_name_char(*c))
+        .take(PLAYER_NAME_MAX_LEN)
+        .collect()
+}
 
 #[derive(Component)]
 pub struct MenuOnlineUI;
 
 #[derive(Component)]
 pub enum MenuOnlineBtn {
+    Name,
     PlayerCountUP,
     PlayerCountDown,
+    GenerateCode,
+    CopyCode,
     LobbyMatch,
     QuickMatch,
     Back,
@@ -36,9 +59,21 @@ pub struct LobbyCodeText;
 #[derive(Resource)]
 pub struct LobbyID(String);
 
+#[derive(Component)]
+pub struct PlayerNameText;
+
+/// Text field that currently receives typed characters, Tab switches between them
+#[derive(Clone, Copy, Default, PartialEq, Resource)]
+pub enum TextFocus {
+    #[default]
+    LobbyID,
+    Name,
+}
+
 pub fn setup_ui(mut commands: Commands, font_assets: Res<FontAssets>) {
-    // lobby id resource
-    commands.insert_resource(LobbyID("".to_owned()));
+    // lobby id resource, prefilled when the page was opened from a shared link
+    commands.insert_resource(LobbyID(lobby_code_from_url().unwrap_or_default()));
+    commands.insert_resource(TextFocus::default());
     // ui camera
     commands
         .spawn(Camera2dBundle::default())
@@ -64,6 +99,51 @@ pub fn setup_ui(mut commands: Commands, font_assets: Res<FontAssets>) {
             ..Default::default()
         })
         .with_children(|parent| {
+            // player name field
+            parent
+                .spawn(ButtonBundle {
+                    style: Style {
+                        width: Val::Px(450.0),
+                        height: Val::Px(65.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::all(Val::Px(16.)),
+                        padding: UiRect::all(Val::Px(16.)),
+                        ..Default::default()
+                    },
+                    background_color: BackgroundColor(NORMAL_BUTTON),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn(TextBundle {
+                            text: Text {
+                                sections: vec![
+                                    TextSection {
+                                        value: "Name: ".to_owned(),
+                                        style: TextStyle {
+                                            font: font_assets.fira_sans.clone(),
+                                            font_size: 40.0,
+                                            color: BUTTON_TEXT,
+                                        },
+                                    },
+                                    TextSection {
+                                        value: "".to_owned(),
+                                        style: TextStyle {
+                                            font: font_assets.fira_sans.clone(),
+                                            font_size: 40.0,
+                                            color: BUTTON_TEXT,
+                                        },
+                                    },
+                                ],
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        })
+                        .insert(PlayerNameText);
+                })
+                .insert(MenuOnlineBtn::Name);
+
             // player count buttons
             parent
                 .spawn(TextBundle {
@@ -206,7 +286,7 @@ pub fn setup_ui(mut commands: Commands, font_assets: Res<FontAssets>) {
                     text: Text {
                         sections: vec![
                             TextSection {
-                                value: "Enter a 4-digit ID!\n".to_owned(),
+                                value: lobby_code_prompt(),
                                 style: TextStyle {
                                     font: font_assets.fira_sans.clone(),
                                     font_size: 40.0,
@@ -228,6 +308,58 @@ pub fn setup_ui(mut commands: Commands, font_assets: Res<FontAssets>) {
                 })
                 .insert(LobbyCodeText);
 
+            // generate and copy lobby code buttons
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..Default::default()
+                    },
+                    background_color: BackgroundColor(Color::NONE),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    for (label, btn) in [
+                        ("Generate", MenuOnlineBtn::GenerateCode),
+                        ("Copy", MenuOnlineBtn::CopyCode),
+                    ] {
+                        let copy = matches!(btn, MenuOnlineBtn::CopyCode);
+                        let mut entity = parent.spawn(ButtonBundle {
+                            style: Style {
+                                width: Val::Px(180.0),
+                                height: Val::Px(55.0),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                margin: UiRect::all(Val::Px(8.)),
+                                ..Default::default()
+                            },
+                            background_color: BackgroundColor(NORMAL_BUTTON),
+                            ..Default::default()
+                        });
+                        entity
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle {
+                                    text: Text::from_section(
+                                        label,
+                                        TextStyle {
+                                            font: font_assets.fira_sans.clone(),
+                                            font_size: 32.0,
+                                            color: BUTTON_TEXT,
+                                        },
+                                    ),
+                                    ..Default::default()
+                                });
+                            })
+                            .insert(btn);
+                        // there is nothing to copy until the code is complete
+                        if copy {
+                            entity.insert(ButtonEnabled(false));
+                        }
+                    }
+                });
+
             // lobby match button
             parent
                 .spawn(ButtonBundle {
@@ -292,15 +424,75 @@ pub fn setup_ui(mut commands: Commands, font_assets: Res<FontAssets>) {
         .insert(MenuOnlineUI);
 }
 
+pub fn switch_text_focus(keys: Res<Input<KeyCode>>, mut focus: ResMut<TextFocus>) {
+    if keys.just_pressed(KeyCode::Tab) {
+        *focus = match *focus {
+            TextFocus::LobbyID => TextFocus::Name,
+            TextFocus::Name => TextFocus::LobbyID,
+        };
+    }
+}
+
+pub fn update_player_name(
+    mut char_evr: EventReader<ReceivedCharacter>,
+    keys: Res<Input<KeyCode>>,
+    mut focus: ResMut<TextFocus>,
+    mut profile: ResMut<PlayerProfile>,
+) {
+    if *focus != TextFocus::Name {
+        char_evr.clear();
+        return;
+    }
+    let name = &mut profile.name;
+    for ev in char_evr.read() {
+        if name.chars().count() < PLAYER_NAME_MAX_LEN && is_valid_name_char(ev.char) {
+            name.push(ev.char);
+        }
+    }
+    if keys.just_pressed(KeyCode::Back) {
+        name.pop();
+    }
+    if keys.just_pressed(KeyCode::Return) {
+        *focus = TextFocus::LobbyID;
+    }
+}
+
+pub fn update_player_name_display(
+    mut query: Query<&mut Text, With<PlayerNameText>>,
+    profile: Res<PlayerProfile>,
+    focus: Res<TextFocus>,
+) {
+    for mut text in query.iter_mut() {
+        text.sections[1].value = profile.name.clone();
+        if *focus == TextFocus::Name {
+            text.sections[1].value.push('_');
+        }
+    }
+}
+
+pub fn save_player_name(mut profile: ResMut<PlayerProfile>) {
+    let trimmed = profile.name.trim().to_owned();
+    profile.name = trimmed;
+    profile.save();
+}
+
 pub fn update_lobby_id(
     mut char_evr: EventReader<ReceivedCharacter>,
     keys: Res<Input<KeyCode>>,
+    focus: Res<TextFocus>,
     mut lobby_id: ResMut<LobbyID>,
 ) {
+    if *focus != TextFocus::LobbyID {
+        char_evr.clear();
+        return;
+    }
     let lid = &mut lobby_id.0;
     for ev in char_evr.read() {
-        if lid.len() < 4 && ev.char.is_ascii_digit() {
-            lid.push(ev.char);
+        if lid.len() >= LOBBY_CODE_LEN {
+            continue;
+        }
+        if let Some(c) = normalize_code_char(ev.char) {
+            lid.push(c);
         }
     }
     if keys.just_pressed(KeyCode::Back) {
@@ -325,7 +517,7 @@ pub fn update_lobby_btn(
 ) {
     let mut lobby_id_complete = false;
     for text in text_query.iter() {
-        if text.sections[1].value.len() == 4 {
+        if is_valid_lobby_code(&text.sections[1].value) {
             lobby_id_complete = true;
             break;
         }
@@ -365,15 +557,21 @@ pub fn btn_visuals(
     }
 }
 
+fn lobby_code_prompt() -> String {
+    format!("Enter a {}-character code!\n", LOBBY_CODE_LEN)
+}
+
 pub fn btn_listeners(
     mut commands: Commands,
     mut state: ResMut<NextState<AppState>>,
-    lobby_id: Res<LobbyID>,
+    mut focus: ResMut<TextFocus>,
+    mut lobby_id: ResMut<LobbyID>,
     mut player_count: ResMut<PlayerCount>,
     mut interaction_query: Query<
         (&Interaction, &MenuOnlineBtn, Option<&ButtonEnabled>),
         Changed<Interaction>,
     >,
+    mut code_text_query: Query<&mut Text, With<LobbyCodeText>>,
 ) {
     for (interaction, btn, enabled) in interaction_query.iter_mut() {
         let clickable = match enabled {
@@ -387,6 +585,9 @@ pub fn btn_listeners(
 
         if let Interaction::Pressed = *interaction {
             match btn {
+                MenuOnlineBtn::Name => {
+                    *focus = TextFocus::Name;
+                }
                 MenuOnlineBtn::PlayerCountUP => {
                     if player_count.0 < MAX_PLAYERS {
                         player_count.0 += 1
@@ -397,15 +598,38 @@ pub fn btn_listeners(
                         player_count.0 -= 1
                     }
                 }
+                MenuOnlineBtn::GenerateCode => {
+                    lobby_id.0 = generate_lobby_code();
+                    *focus = TextFocus::LobbyID;
+                    for mut text in code_text_query.iter_mut() {
+                        text.sections[0].value = lobby_code_prompt();
+                    }
+                }
+                MenuOnlineBtn::CopyCode => {
+                    let hint = if copy_to_clipboard(&share_text(&lobby_id.0)) {
+                        "Copied to clipboard!\n"
+                    } else {
+                        "Unable to copy, share the code below\n"
+                    };
+                    for mut text in code_text_query.iter_mut() {
+                        text.sections[0].value = hint.to_owned();
+                    }
+                }
                 MenuOnlineBtn::LobbyMatch => {
+                    set_lobby_code_in_url(&lobby_id.0);
                     commands.insert_resource(ConnectData {
-                        lobby_id: format!("turtletime_{}_{}", VERSION, lobby_id.0),
+                        // private lobbies are shared across versions so the handshake can
+                        // tell mismatched friends why they can't play together
+                        lobby_id: format!("turtletime_{}", lobby_id.0),
                     });
                     state.set(AppState::MenuConnect);
                 }
                 MenuOnlineBtn::QuickMatch => {
                     commands.insert_resource(ConnectData {
-                        lobby_id: format!("turtletime_{}?next={}", VERSION, player_count.0),
+                        lobby_id: format!(
+                            "turtletime_p{}?next={}",
+                            PROTOCOL_VERSION, player_count.0
+                        ),
                     });
                     state.set(AppState::MenuConnect);
                 }
@@ -431,3 +655,18 @@ pub fn cleanup_ui(query: Query<Entity, With<MenuOnlineUI>>, mut commands: Comman
         commands.entity(e).despawn_recursive();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitize_counts_characters_not_bytes() {
+        let name = "ééééééééééééééé";
+        assert_eq!(sanitize_player_name(name), "");
+
+        let name = "Turtleé_Tim 12345";
+        assert_eq!(sanitize_player_name(name), "Turtle_Tim 1");
+        assert_eq!(sanitize_player_name("  Shelly  "), "Shelly");
+    }
+}
@@ -1,5 +1,7 @@
 use super::plugin::{BUTTON_TEXT, HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON};
 use crate::loading::FontAssets;
+use crate::player::resources::{CHECK_DISTANCE_MIN, MAX_PREDICTION_MAX, MAX_PREDICTION_MIN};
+use crate::stats::profile::PlayerProfile;
 use crate::AppState;
 use bevy::prelude::*;
 
@@ -8,9 +10,100 @@ pub struct MenuOptionsUI;
 
 #[derive(Component)]
 pub enum MenuOptionsBtn {
+    DelayDown,
+    DelayUp,
+    PredictionDown,
+    PredictionUp,
+    CheckDistanceDown,
+    CheckDistanceUp,
     Back,
 }
 
+#[derive(Component)]
+pub enum NetcodeOptionText {
+    InputDelay,
+    MaxPrediction,
+    CheckDistance,
+}
+
+fn spawn_arrow_btn(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    label: &str,
+    btn: MenuOptionsBtn,
+) {
+    parent
+        .spawn(ButtonBundle {
+            style: Style {
+                width: Val::Px(50.0),
+                height: Val::Px(50.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                margin: UiRect::all(Val::Px(6.)),
+                ..Default::default()
+            },
+            background_color: BackgroundColor(NORMAL_BUTTON),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent.spawn(TextBundle {
+                text: Text::from_section(
+                    label,
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 30.0,
+                        color: BUTTON_TEXT,
+                    },
+                ),
+                ..Default::default()
+            });
+        })
+        .insert(btn);
+}
+
+/// One netcode setting with a decrease button, its current value and an increase button
+fn spawn_option_row(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    text: NetcodeOptionText,
+    down: MenuOptionsBtn,
+    up: MenuOptionsBtn,
+) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..Default::default()
+            },
+            background_color: BackgroundColor(Color::NONE),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            spawn_arrow_btn(parent, font, "-", down);
+            parent
+                .spawn(TextBundle {
+                    style: Style {
+                        width: Val::Px(320.),
+                        ..Default::default()
+                    },
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 30.0,
+                            color: BUTTON_TEXT,
+                        },
+                    )
+                    .with_alignment(TextAlignment::Center),
+                    ..Default::default()
+                })
+                .insert(text);
+            spawn_arrow_btn(parent, font, "+", up);
+        });
+}
+
 pub fn setup_ui(mut commands: Commands, font_assets: Res<FontAssets>) {
     // ui camera
     commands
@@ -72,12 +165,59 @@ pub fn setup_ui(mut commands: Commands, font_assets: Res<FontAssets>) {
                                 color: BUTTON_TEXT,
                             },
                         },
+                        TextSection {
+                            value: "Drop poop: [Q]\n".to_owned(),
+                            style: TextStyle {
+                                font: font_assets.fira_sans.clone(),
+                                font_size: 30.0,
+                                color: BUTTON_TEXT,
+                            },
+                        },
                     ],
                     ..Default::default()
                 },
                 ..Default::default()
             });
 
+            // netcode settings, used when hosting a lobby and for local matches
+            parent.spawn(TextBundle {
+                style: Style {
+                    margin: UiRect::top(Val::Px(24.)),
+                    ..Default::default()
+                },
+                text: Text::from_section(
+                    "Netcode:",
+                    TextStyle {
+                        font: font_assets.fira_sans.clone(),
+                        font_size: 40.0,
+                        color: BUTTON_TEXT,
+                    },
+                ),
+                ..Default::default()
+            });
+            let font = &font_assets.fira_sans;
+            spawn_option_row(
+                parent,
+                font,
+                NetcodeOptionText::InputDelay,
+                MenuOptionsBtn::DelayDown,
+                MenuOptionsBtn::DelayUp,
+            );
+            spawn_option_row(
+                parent,
+                font,
+                NetcodeOptionText::MaxPrediction,
+                MenuOptionsBtn::PredictionDown,
+                MenuOptionsBtn::PredictionUp,
+            );
+            spawn_option_row(
+                parent,
+                font,
+                NetcodeOptionText::CheckDistance,
+                MenuOptionsBtn::CheckDistanceDown,
+                MenuOptionsBtn::CheckDistanceUp,
+            );
+
             // back button
             parent
                 .spawn(ButtonBundle {
@@ -113,11 +253,39 @@ pub fn setup_ui(mut commands: Commands, font_assets: Res<FontAssets>) {
 
 pub fn btn_listeners(
     mut state: ResMut<NextState<AppState>>,
+    mut profile: ResMut<PlayerProfile>,
     mut interaction_query: Query<(&Interaction, &MenuOptionsBtn), Changed<Interaction>>,
 ) {
     for (interaction, btn) in interaction_query.iter_mut() {
         if let Interaction::Pressed = *interaction {
+            let netcode = &mut profile.netcode;
             match btn {
+                MenuOptionsBtn::DelayDown => {
+                    netcode.input_delay = netcode.input_delay.prev();
+                }
+                MenuOptionsBtn::DelayUp => {
+                    netcode.input_delay = netcode.input_delay.next();
+                }
+                MenuOptionsBtn::PredictionDown => {
+                    netcode.max_prediction = netcode
+                        .max_prediction
+                        .saturating_sub(1)
+                        .max(MAX_PREDICTION_MIN);
+                    netcode.check_distance = netcode.check_distance();
+                }
+                MenuOptionsBtn::PredictionUp => {
+                    netcode.max_prediction = (netcode.max_prediction + 1).min(MAX_PREDICTION_MAX);
+                }
+                MenuOptionsBtn::CheckDistanceDown => {
+                    netcode.check_distance = netcode
+                        .check_distance
+                        .saturating_sub(1)
+                        .max(CHECK_DISTANCE_MIN);
+                }
+                MenuOptionsBtn::CheckDistanceUp => {
+                    netcode.check_distance += 1;
+                    netcode.check_distance = netcode.check_distance();
+                }
                 MenuOptionsBtn::Back => {
                     state.set(AppState::MenuMain);
                 }
@@ -147,6 +315,30 @@ pub fn btn_visuals(
     }
 }
 
+pub fn update_netcode_text(
+    profile: Res<PlayerProfile>,
+    mut text_query: Query<(&NetcodeOptionText, &mut Text)>,
+) {
+    let netcode = &profile.netcode;
+    for (option, mut text) in text_query.iter_mut() {
+        text.sections[0].value = match option {
+            NetcodeOptionText::InputDelay => {
+                format!("Input delay: {}", netcode.describe_input_delay())
+            }
+            NetcodeOptionText::MaxPrediction => {
+                format!("Max prediction: {}", netcode.max_prediction)
+            }
+            NetcodeOptionText::CheckDistance => {
+                format!("Check distance: {}", netcode.check_distance)
+            }
+        };
+    }
+}
+
+pub fn save_options(profile: Res<PlayerProfile>) {
+    profile.save();
+}
+
 pub fn cleanup_ui(query: Query<Entity, With<MenuOptionsUI>>, mut commands: Commands) {
     for e in query.iter() {
         commands.entity(e).despawn_recursive();
@@ -1,6 +1,9 @@
-use crate::menu::{connect, main, online, options, win};
+use crate::menu::lobby::LobbyRoom;
+use crate::menu::{connect, leaderboard, lobby, main, online, options, win};
 use crate::AppState;
 use bevy::prelude::*;
+use bevy_matchbox::prelude::MultipleChannels;
+use bevy_matchbox::MatchboxSocket;
 
 pub const DISABLED_BUTTON: Color = Color::rgb(0.8, 0.5, 0.5);
 pub const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
@@ -17,7 +20,10 @@ impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
         app
             // main menu
-            .add_systems(OnEnter(AppState::MenuMain), main::setup_ui)
+            .add_systems(
+                OnEnter(AppState::MenuMain),
+                (connect::close_socket, main::setup_ui),
+            )
             .add_systems(
                 Update,
                 (main::btn_visuals, main::btn_listeners).run_if(in_state(AppState::MenuMain)),
@@ -28,6 +34,9 @@ impl Plugin for MenuPlugin {
             .add_systems(
                 Update,
                 (
+                    online::switch_text_focus,
+                    online::update_player_name,
+                    online::update_player_name_display,
                     online::update_lobby_id,
                     online::update_lobby_id_display,
                     online::update_lobby_btn,
@@ -37,7 +46,10 @@ impl Plugin for MenuPlugin {
                 )
                     .run_if(in_state(AppState::MenuOnline)),
             )
-            .add_systems(OnExit(AppState::MenuOnline), online::cleanup_ui)
+            .add_systems(
+                OnExit(AppState::MenuOnline),
+                (online::save_player_name, online::cleanup_ui),
+            )
             // connect menu
             .add_systems(
                 OnEnter(AppState::MenuConnect),
@@ -46,26 +58,79 @@ impl Plugin for MenuPlugin {
             .add_systems(
                 Update,
                 (
-                    connect::lobby_system,
-                    connect::btn_visuals,
                     connect::btn_listeners,
+                    lobby::lobby_network_system
+                        .run_if(resource_exists::<MatchboxSocket<MultipleChannels>>()),
+                    connect::lobby_system,
+                    connect::update_lobby_ui,
                 )
+                    .chain()
                     .run_if(in_state(AppState::MenuConnect)),
             )
+            .add_systems(
+                Update,
+                connect::btn_visuals.run_if(in_state(AppState::MenuConnect)),
+            )
             .add_systems(OnExit(AppState::MenuConnect), connect::cleanup_ui)
             // options menu
             .add_systems(OnEnter(AppState::MenuOptions), options::setup_ui)
             .add_systems(
                 Update,
-                (options::btn_visuals, options::btn_listeners)
+                (
+                    options::btn_visuals,
+                    (options::btn_listeners, options::update_netcode_text).chain(),
+                )
                     .run_if(in_state(AppState::MenuOptions)),
             )
-            .add_systems(OnExit(AppState::MenuOptions), options::cleanup_ui)
+            .add_systems(
+                OnExit(AppState::MenuOptions),
+                (options::save_options, options::cleanup_ui),
+            )
+            // leaderboard menu
+            .add_systems(OnEnter(AppState::MenuLeaderboard), leaderboard::setup_ui)
+            .add_systems(
+                Update,
+                (leaderboard::btn_visuals, leaderboard::btn_listeners)
+                    .run_if(in_state(AppState::MenuLeaderboard)),
+            )
+            .add_systems(OnExit(AppState::MenuLeaderboard), leaderboard::cleanup_ui)
             // win menu
             .add_systems(OnEnter(AppState::Win), win::setup_ui)
             .add_systems(
                 Update,
-                (win::btn_visuals, win::btn_listeners).run_if(in_state(AppState::Win)),
+                (
+                    win::btn_visuals,
+                    win::btn_listeners,
+                    win::animate_winner_sprite,
+                )
+                    .run_if(in_state(AppState::Win)),
+            )
+            // peers joining mid-round are greeted and get the round's inputs to spectate it
+            .add_systems(
+                Update,
+                (
+                    lobby::lobby_network_system,
+                    lobby::stream_round_to_spectators,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::RoundOnline))
+                    .run_if(resource_exists::<LobbyRoom>())
+                    .run_if(resource_exists::<MatchboxSocket<MultipleChannels>>()),
+            )
+            // rematch votes travel over the lobby channel of the socket kept from the last round
+            .add_systems(
+                Update,
+                (
+                    lobby::lobby_network_system,
+                    lobby::stream_round_to_spectators,
+                    win::rematch_system,
+                    win::update_rematch_ui,
+                )
+                    .chain()
+                    .after(win::btn_listeners)
+                    .run_if(in_state(AppState::Win))
+                    .run_if(resource_exists::<LobbyRoom>())
+                    .run_if(resource_exists::<MatchboxSocket<MultipleChannels>>()),
             )
             .add_systems(OnExit(AppState::Win), win::cleanup_ui);
     }
@@ -1,8 +1,23 @@
-use super::connect::ConnectData;
+use super::connect::{
+    start_p2p_session, ConnectData, ConnectError, ConnectStatus, PEER_SILENCE_TIMEOUT_SECS,
+};
+use super::lobby::LobbyRoom;
 use super::plugin::{BUTTON_TEXT, HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON};
+use crate::graphics::{CharacterSheet, FrameAnimation};
 use crate::loading::FontAssets;
-use crate::AppState;
+use crate::player::resources::PlayerNames;
+use crate::stats::profile::PlayerProfile;
+use crate::stats::resources::{ordinal, PlayerStats, RoundStats};
+use crate::{AppState, GameState, FPS, TILE_SIZE};
 use bevy::prelude::*;
+use bevy_matchbox::prelude::MultipleChannels;
+use bevy_matchbox::MatchboxSocket;
+
+const RESULTS_COLUMNS: [&str; 8] = [
+    "Place", "Player", "Kills", "Deaths", "Dealt", "Taken", "Pickups", "Out at",
+];
+const RESULTS_COLUMN_WIDTH: f32 = 95.;
+const RESULTS_NAME_WIDTH: f32 = 160.;
 
 #[derive(Component)]
 pub struct WinUI;
@@ -13,14 +28,152 @@ pub enum MenuWinBtn {
     Rematch,
 }
 
+#[derive(Component)]
+pub struct WinnerSprite;
+
+#[derive(Component)]
+pub struct RematchBtnText;
+
 #[derive(Resource)]
 pub struct MatchData {
     pub result: String,
+    pub local_handle: usize,
+    pub stats: RoundStats,
+}
+
+/// One row of the post-match results table
+#[derive(Clone, Debug)]
+pub struct PlayerResult {
+    pub handle: usize,
+    pub name: String,
+    pub placement: usize,
+    pub eliminated_frame: Option<i32>,
+    pub kills: i32,
+    pub deaths: i32,
+    pub damage_dealt: i32,
+    pub damage_taken: i32,
+    pub pickups: u32,
+}
+
+impl PlayerResult {
+    fn columns(&self) -> [String; 8] {
+        let eliminated = match self.eliminated_frame {
+            Some(frame) => {
+                let secs = frame.max(0) as usize / FPS;
+                format!("{}:{:02}", secs / 60, secs % 60)
+            }
+            None => "-".to_owned(),
+        };
+        [
+            ordinal(self.placement),
+            self.name.clone(),
+            self.kills.to_string(),
+            self.deaths.to_string(),
+            self.damage_dealt.to_string(),
+            self.damage_taken.to_string(),
+            self.pickups.to_string(),
+            eliminated,
+        ]
+    }
+}
+
+/// Final standings for every player in the round, sorted by placement
+#[derive(Clone, Debug, Resource)]
+pub struct MatchResults {
+    pub winner: usize,
+    pub players: Vec<PlayerResult>,
+}
+
+impl MatchResults {
+    pub fn from_stats(stats: &RoundStats, names: &PlayerNames, winner: usize) -> MatchResults {
+        let mut players = stats
+            .players
+            .iter()
+            .map(|s| PlayerResult {
+                handle: s.handle,
+                name: names.get(s.handle),
+                placement: s.placement,
+                eliminated_frame: s.eliminated_frame,
+                kills: s.kills,
+                deaths: s.deaths,
+                damage_dealt: s.damage_dealt,
+                damage_taken: s.damage_taken,
+                pickups: s.edibles_total(),
+            })
+            .collect::<Vec<_>>();
+        players.sort_by_key(|p| p.placement);
+
+        MatchResults { winner, players }
+    }
+}
+
+fn round_summary(stats: &PlayerStats) -> String {
+    let edibles = stats
+        .edibles_eaten
+        .iter()
+        .map(|(edible, count)| format!("{} x{}", edible, count))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        "Fireballs hit: {} / {}\nPoop damage dealt: {}\nGeese defeated: {}\nEdibles eaten: {}\nDistance moved: {:.0} tiles",
+        stats.fireballs_hit,
+        stats.fireballs_fired,
+        stats.poop_damage_dealt,
+        stats.geese_killed,
+        if edibles.is_empty() { "none".to_owned() } else { edibles },
+        stats.distance_moved / TILE_SIZE,
+    )
+}
+
+fn spawn_results_row(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    columns: &[String],
+    font_size: f32,
+    color: Color,
+) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                ..Default::default()
+            },
+            background_color: BackgroundColor(Color::NONE),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            for (i, column) in columns.iter().enumerate() {
+                // the player name column needs more room than the numbers
+                let width = if i == 1 {
+                    RESULTS_NAME_WIDTH
+                } else {
+                    RESULTS_COLUMN_WIDTH
+                };
+                parent.spawn(TextBundle {
+                    style: Style {
+                        width: Val::Px(width),
+                        ..Default::default()
+                    },
+                    text: Text::from_section(
+                        column.clone(),
+                        TextStyle {
+                            font: font.clone(),
+                            font_size,
+                            color,
+                        },
+                    ),
+                    ..Default::default()
+                });
+            }
+        });
 }
 
 pub fn setup_ui(
     mut commands: Commands,
     match_data: Res<MatchData>,
+    match_results: Option<Res<MatchResults>>,
+    characters: Res<CharacterSheet>,
     font_assets: Res<FontAssets>,
     connect_data: Option<Res<ConnectData>>,
 ) {
@@ -52,6 +205,27 @@ pub fn setup_ui(
             ..Default::default()
         })
         .with_children(|parent| {
+            // winning turtle
+            parent
+                .spawn(AtlasImageBundle {
+                    style: Style {
+                        width: Val::Px(TILE_SIZE * 4.),
+                        height: Val::Px(TILE_SIZE * 4.),
+                        ..Default::default()
+                    },
+                    texture_atlas: characters.turtle_handle.clone(),
+                    texture_atlas_image: UiTextureAtlasImage {
+                        index: characters.turtle_frames[0],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .insert(FrameAnimation {
+                    timer: Timer::from_seconds(0.2, TimerMode::Repeating),
+                    frames: characters.turtle_frames.to_vec(),
+                    current_frame: 0,
+                })
+                .insert(WinnerSprite);
             // match result string
             parent.spawn(TextBundle {
                 style: Style {
@@ -63,23 +237,88 @@ pub fn setup_ui(
                     match_data.result.clone(),
                     TextStyle {
                         font: font_assets.fira_sans.clone(),
-                        font_size: 96.,
+                        font_size: 64.,
                         color: BUTTON_TEXT,
                     },
                 ),
                 ..Default::default()
             });
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::Start,
+                        margin: UiRect::all(Val::Px(16.)),
+                        ..Default::default()
+                    },
+                    background_color: BackgroundColor(Color::NONE),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    // results table
+                    if let Some(results) = match_results.as_ref() {
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::Column,
+                                    margin: UiRect::right(Val::Px(32.)),
+                                    ..Default::default()
+                                },
+                                background_color: BackgroundColor(Color::NONE),
+                                ..Default::default()
+                            })
+                            .with_children(|parent| {
+                                let header = RESULTS_COLUMNS.map(|c| c.to_owned());
+                                spawn_results_row(
+                                    parent,
+                                    &font_assets.fira_sans,
+                                    &header,
+                                    24.,
+                                    Color::GRAY,
+                                );
+                                for player in results.players.iter() {
+                                    let color = if player.handle == results.winner {
+                                        Color::GOLD
+                                    } else if player.handle == match_data.local_handle {
+                                        Color::GREEN
+                                    } else {
+                                        BUTTON_TEXT
+                                    };
+                                    spawn_results_row(
+                                        parent,
+                                        &font_assets.fira_sans,
+                                        &player.columns(),
+                                        24.,
+                                        color,
+                                    );
+                                }
+                            });
+                    }
+                    // local player round stats
+                    if let Some(stats) = match_data.stats.players.get(match_data.local_handle) {
+                        parent.spawn(TextBundle {
+                            text: Text::from_section(
+                                round_summary(stats),
+                                TextStyle {
+                                    font: font_assets.fira_sans.clone(),
+                                    font_size: 24.,
+                                    color: BUTTON_TEXT,
+                                },
+                            ),
+                            ..Default::default()
+                        });
+                    }
+                });
             // rematch button
             parent
                 .spawn(ButtonBundle {
                     style: Style {
-                        width: Val::Px(250.),
+                        width: Val::Px(320.),
                         height: Val::Px(65.0),
                         justify_content: JustifyContent::Center,
                         align_items: AlignItems::Center,
                         margin: UiRect::all(Val::Px(16.)),
                         padding: UiRect::all(Val::Px(16.)),
-
                         ..Default::default()
                     },
                     visibility: rematch_vis,
@@ -87,17 +326,19 @@ pub fn setup_ui(
                     ..Default::default()
                 })
                 .with_children(|parent| {
-                    parent.spawn(TextBundle {
-                        text: Text::from_section(
-                            "Rematch",
-                            TextStyle {
-                                font: font_assets.fira_sans.clone(),
-                                font_size: 40.0,
-                                color: BUTTON_TEXT,
-                            },
-                        ),
-                        ..Default::default()
-                    });
+                    parent
+                        .spawn(TextBundle {
+                            text: Text::from_section(
+                                "Rematch",
+                                TextStyle {
+                                    font: font_assets.fira_sans.clone(),
+                                    font_size: 40.0,
+                                    color: BUTTON_TEXT,
+                                },
+                            ),
+                            ..Default::default()
+                        })
+                        .insert(RematchBtnText);
                 })
                 .insert(MenuWinBtn::Rematch);
             // back to menu button
@@ -133,6 +374,20 @@ pub fn setup_ui(
         .insert(WinUI);
 
     commands.remove_resource::<MatchData>();
+    commands.remove_resource::<MatchResults>();
+}
+
+pub fn animate_winner_sprite(
+    time: Res<Time>,
+    mut query: Query<(&mut UiTextureAtlasImage, &mut FrameAnimation), With<WinnerSprite>>,
+) {
+    for (mut image, mut animation) in query.iter_mut() {
+        animation.timer.tick(time.delta());
+        if animation.timer.just_finished() {
+            animation.current_frame = (animation.current_frame + 1) % animation.frames.len();
+            image.index = animation.frames[animation.current_frame];
+        }
+    }
 }
 
 pub fn btn_visuals(
@@ -158,22 +413,80 @@ pub fn btn_visuals(
 
 pub fn btn_listeners(
     mut app_state: ResMut<NextState<AppState>>,
+    mut room: Option<ResMut<LobbyRoom>>,
+    status: Option<Res<ConnectStatus>>,
     mut interaction_query: Query<(&Interaction, &MenuWinBtn), Changed<Interaction>>,
 ) {
+    let connected = status.is_some_and(|s| s.error.is_none());
     for (interaction, btn) in interaction_query.iter_mut() {
         if let Interaction::Pressed = *interaction {
             match btn {
                 MenuWinBtn::Back => {
                     app_state.set(AppState::MenuMain);
                 }
-                MenuWinBtn::Rematch => {
-                    app_state.set(AppState::MenuConnect);
-                }
+                MenuWinBtn::Rematch => match room.as_mut() {
+                    Some(room) if connected && !room.peers.is_empty() => room.vote_rematch(),
+                    // everyone else left, look for a new match instead
+                    _ => app_state.set(AppState::MenuConnect),
+                },
             }
         }
     }
 }
 
+/// Starts the next round on the existing connection once everyone voted for a rematch
+#[allow(clippy::too_many_arguments)]
+pub fn rematch_system(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut socket: ResMut<MatchboxSocket<MultipleChannels>>,
+    mut room: ResMut<LobbyRoom>,
+    mut status: ResMut<ConnectStatus>,
+    mut app_state: ResMut<NextState<AppState>>,
+    mut game_state: ResMut<NextState<GameState>>,
+    profile: Res<PlayerProfile>,
+) {
+    if status.error.is_none() {
+        let now = time.elapsed_seconds_f64();
+        if let Some(peer) = room.silent_peer(now, PEER_SILENCE_TIMEOUT_SECS) {
+            status.fail(ConnectError::PeerSilent(room.peer_name(peer)));
+        }
+    }
+    if status.error.is_some() || !room.everyone_wants_rematch() {
+        return;
+    }
+    info!(
+        "everyone voted for a rematch, starting round {}",
+        room.round
+    );
+    match start_p2p_session(&mut commands, &mut socket, &mut room, &profile) {
+        Ok(true) => {
+            app_state.set(AppState::RoundOnline);
+            game_state.set(GameState::Playing);
+        }
+        Ok(false) => (),
+        Err(e) => status.fail(ConnectError::Session(e)),
+    }
+}
+
+pub fn update_rematch_ui(
+    room: Res<LobbyRoom>,
+    status: Res<ConnectStatus>,
+    mut text_query: Query<&mut Text, With<RematchBtnText>>,
+) {
+    let total = room.peers.len() + 1;
+    let label = if status.error.is_some() || room.peers.is_empty() {
+        "Find new match".to_owned()
+    } else if room.voted_rematch() {
+        format!("Waiting {}/{}", room.rematch_count(), total)
+    } else {
+        format!("Rematch {}/{}", room.rematch_count(), total)
+    };
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = label.clone();
+    }
+}
+
 pub fn cleanup_ui(query: Query<Entity, With<WinUI>>, mut commands: Commands) {
     for e in query.iter() {
         commands.entity(e).despawn_recursive();
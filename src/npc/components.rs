@@ -1,15 +1,140 @@
 use bevy::prelude::*;
 
+use crate::physics::{Fixed, FixedVec2};
+use crate::{FPS, TILE_SIZE};
+
+/// Walking speeds in units per second
 pub const GOOSE_SPEED: i32 = 105;
+pub const GOOSE_WANDER_SPEED: i32 = 50;
+pub const GOOSE_CHASE_SPEED: i32 = 120;
+pub const GOOSE_FLEE_SPEED: i32 = 160;
+/// Turtles closer than this get honked at and chased
+pub const GOOSE_AGGRO_RANGE: Fixed = Fixed::from_f32(TILE_SIZE * 3.);
+/// A chased turtle further away than this is given up on
+pub const GOOSE_CHASE_RANGE: Fixed = Fixed::from_f32(TILE_SIZE * 8.);
+pub const GOOSE_CHASE_FRAMES: u32 = 4 * FPS as u32;
+pub const GOOSE_FLEE_FRAMES: u32 = 2 * FPS as u32;
+/// Edibles further away than this are not noticed
+pub const GOOSE_SIGHT_RANGE: Fixed = Fixed::from_f32(TILE_SIZE * 10.);
+/// Geese further than this from their nest head back
+pub const GOOSE_LEASH_RANGE: Fixed = Fixed::from_f32(TILE_SIZE * 15.);
+/// Close enough to count as back at the nest
+pub const GOOSE_NEST_RANGE: Fixed = Fixed::from_f32(TILE_SIZE);
+/// Frames a wandering goose keeps walking in one direction
+pub const GOOSE_WANDER_LEG_FRAMES: u32 = 2 * FPS as u32;
+/// Most geese on the map at once unless the lobby host picks otherwise
+pub const GOOSE_MAX: usize = 3;
+pub const GOOSE_MAX_MIN: usize = 0;
+pub const GOOSE_MAX_MAX: usize = 6;
+/// Seconds between goose spawns while below the maximum
+pub const GOOSE_SPAWN_INTERVAL: f32 = 30.0;
+pub const GOOSE_HEALTH_MAX: i32 = 15;
+/// Seconds a hit goose is drawn tinted
+pub const GOOSE_HIT_FLASH_TIME: f32 = 0.2;
 
 #[derive(Clone, Component, Default, Hash, Reflect)]
 #[reflect(Component, Hash)]
-pub struct Goose;
+pub struct Goose {
+    /// Counts up from 0 in spawn order, the same on every peer
+    pub id: u32,
+}
+
+#[derive(Clone, Component, Copy, Debug, Hash, Reflect)]
+#[reflect(Component, Hash)]
+pub struct GooseHealth(pub i32);
+
+impl Default for GooseHealth {
+    fn default() -> Self {
+        GooseHealth(GOOSE_HEALTH_MAX)
+    }
+}
+
+/// Added when a goose takes damage, removed once the flash is over
+#[derive(Clone, Component, Reflect)]
+#[reflect(Component)]
+pub struct GooseHitFlash {
+    pub timer: Timer,
+}
+
+impl Default for GooseHitFlash {
+    fn default() -> Self {
+        GooseHitFlash {
+            timer: Timer::from_seconds(GOOSE_HIT_FLASH_TIME, TimerMode::Once),
+        }
+    }
+}
 
 #[derive(Clone, Component, Default, Hash, Reflect)]
 #[reflect(Component, Hash)]
 pub struct HasTarget;
 
+/// Edible claimed by the goose with this id, no other goose goes for it
 #[derive(Clone, Component, Default, Hash, Reflect)]
 #[reflect(Component, Hash)]
-pub struct EdibleTarget;
+pub struct EdibleTarget {
+    pub claimed_by: u32,
+}
+
+/// Keeps up to `max` geese on the map, the first one spawns when the round starts and
+/// another one every time the timer finishes
+#[derive(Clone, Debug, Reflect, Resource)]
+#[reflect(Resource)]
+pub struct GoosePopulation {
+    pub max: usize,
+    pub timer: Timer,
+    /// Id of the next goose to spawn
+    pub next_id: u32,
+}
+
+impl Default for GoosePopulation {
+    fn default() -> Self {
+        GoosePopulation::new(GOOSE_MAX)
+    }
+}
+
+impl GoosePopulation {
+    pub fn new(max: usize) -> Self {
+        GoosePopulation {
+            max,
+            timer: Timer::from_seconds(GOOSE_SPAWN_INTERVAL, TimerMode::Repeating),
+            next_id: 0,
+        }
+    }
+}
+
+/// What a goose is doing, turtles are referred to by player handle so the state is the same
+/// on every peer
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, Reflect)]
+pub enum GooseState {
+    #[default]
+    Wander,
+    ChaseEdible,
+    /// Honked at this turtle and chases it
+    ChaseTurtle(usize),
+    /// Runs away from the turtle that hit it
+    Flee(usize),
+    ReturnToNest,
+}
+
+/// Behaviour state of a goose, `geese_think` picks the state and the movement systems act on it
+#[derive(Clone, Component, Debug, Default, Hash, Reflect)]
+#[reflect(Component, Hash)]
+pub struct GooseBrain {
+    pub state: GooseState,
+    pub frames_in_state: u32,
+    /// Where the goose spawned and returns to
+    pub nest: FixedVec2,
+    /// Picks the wander directions, agreed on by all peers when the goose spawns
+    pub wander_seed: u32,
+    /// Counts the directions walked while wandering
+    pub wander_leg: u32,
+}
+
+impl GooseBrain {
+    pub fn set_state(&mut self, state: GooseState) {
+        if self.state != state {
+            self.state = state;
+            self.frames_in_state = 0;
+        }
+    }
+}
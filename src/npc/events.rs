@@ -0,0 +1,10 @@
+use bevy::prelude::*;
+
+/// Sent by the player rollback systems when a turtle hits a goose,
+/// consumed by `damage_geese` and `geese_think` in the same frame.
+#[derive(Clone, Copy, Debug, Event)]
+pub struct GooseHitEvent {
+    pub goose: Entity,
+    pub attacker: usize,
+    pub amount: i32,
+}
@@ -6,8 +6,11 @@ use crate::{
     GameState,
 };
 
+use super::components::GoosePopulation;
+use super::events::GooseHitEvent;
 use super::systems::{
-    geese_target_closest_edible, goose_ate_edible, move_geese_toward_target, spawn_geese,
+    damage_geese, flash_hit_geese, geese_target_closest_edible, geese_think, goose_ate_edible,
+    move_geese, move_geese_toward_target, setup_geese, spawn_geese_over_time, tick_goose_hit_flash,
 };
 
 pub struct GoosePlugin;
@@ -19,12 +22,20 @@ pub struct NpcSystemSet;
 /// Player logic is only active during the State `GameState::Playing`
 impl Plugin for GoosePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Playing), spawn_geese)
+        app.add_event::<GooseHitEvent>()
+            .init_resource::<GoosePopulation>()
+            .add_systems(OnEnter(GameState::Playing), setup_geese)
+            .add_systems(Update, flash_hit_geese.run_if(in_state(GameState::Playing)))
             .add_systems(
                 GgrsSchedule,
                 (
+                    damage_geese,
+                    tick_goose_hit_flash,
+                    spawn_geese_over_time,
+                    geese_think,
                     geese_target_closest_edible,
                     move_geese_toward_target,
+                    move_geese,
                     goose_ate_edible,
                 )
                     .chain()
@@ -1,166 +1,545 @@
-use bevy::{math::Vec3Swizzles, prelude::*};
+use std::f32::consts::FRAC_1_SQRT_2;
+use std::time::Duration;
+
+use bevy::core::FrameCount;
+use bevy::prelude::*;
 use bevy_ggrs::AddRollbackCommandExtension;
 use rand::Rng;
 
 use crate::{
+    audio::{RollbackSound, RollbackSoundBundle},
     debug,
     graphics::{CharacterSheet, FrameAnimation},
-    map::tilemap::EncounterSpawner,
+    loading::AudioAssets,
+    map::tilemap::{EncounterSpawner, TileCollider},
     npc::components::{EdibleTarget, Goose, HasTarget},
+    physics::{Fixed, FixedVec2, Position},
     player::{
-        components::{Edible, Expired, RoundComponent},
-        resources::AgreedRandom,
+        components::{Edible, Expired, Player, RoundComponent},
+        edibles::EdibleRegistry,
+        resources::{AgreedRandom, MatchSettings},
+        status::{StatusEffects, StatusKind},
+        systems::{spawn_edible, wall_collision_check},
     },
-    FPS, TILE_SIZE,
+    stats::resources::RoundStats,
+    FIXED_TICK_MS, FPS, TILE_SIZE,
+};
+
+use super::components::{
+    GooseBrain, GooseHealth, GooseHitFlash, GoosePopulation, GooseState, GOOSE_AGGRO_RANGE,
+    GOOSE_CHASE_FRAMES, GOOSE_CHASE_RANGE, GOOSE_CHASE_SPEED, GOOSE_FLEE_FRAMES, GOOSE_FLEE_SPEED,
+    GOOSE_LEASH_RANGE, GOOSE_NEST_RANGE, GOOSE_SIGHT_RANGE, GOOSE_SPEED, GOOSE_WANDER_LEG_FRAMES,
+    GOOSE_WANDER_SPEED,
 };
+use super::events::GooseHitEvent;
+
+const DIAGONAL: Fixed = Fixed::from_f32(FRAC_1_SQRT_2);
+const NEG_DIAGONAL: Fixed = Fixed::from_f32(-FRAC_1_SQRT_2);
+/// Wandering geese pick one of the eight compass directions
+const WANDER_DIRS: [FixedVec2; 8] = [
+    FixedVec2::new(Fixed::ONE, Fixed::ZERO),
+    FixedVec2::new(DIAGONAL, DIAGONAL),
+    FixedVec2::new(Fixed::ZERO, Fixed::ONE),
+    FixedVec2::new(NEG_DIAGONAL, DIAGONAL),
+    FixedVec2::new(Fixed::from_int(-1), Fixed::ZERO),
+    FixedVec2::new(NEG_DIAGONAL, NEG_DIAGONAL),
+    FixedVec2::new(Fixed::ZERO, Fixed::from_int(-1)),
+    FixedVec2::new(DIAGONAL, NEG_DIAGONAL),
+];
 
-use super::components::GOOSE_SPEED;
+type VisibleEdible = (With<Edible>, Without<Expired>, Without<Goose>);
+type UnclaimedEdible = (With<Edible>, Without<Expired>, Without<EdibleTarget>);
+type ClaimedEdible = (Without<Expired>, Without<Goose>);
+type ChasingGoose = (
+    Entity,
+    &'static Goose,
+    &'static mut Position,
+    &'static mut TextureAtlasSprite,
+    &'static GooseBrain,
+);
 
-pub fn spawn_geese(
+/// Every round starts with a fresh population capped by the match settings
+pub fn setup_geese(mut commands: Commands, settings: Res<MatchSettings>) {
+    commands.insert_resource(GoosePopulation::new(settings.geese_max));
+}
+
+/// Spawns a goose at a random encounter spawner whenever the population timer finishes and
+/// there is room for one more
+pub fn spawn_geese_over_time(
     mut commands: Commands,
     characters: Res<CharacterSheet>,
+    mut population: ResMut<GoosePopulation>,
     mut agreed_seed: ResMut<AgreedRandom>,
-    spawner_query: Query<&Transform, With<EncounterSpawner>>,
+    spawner_query: Query<(Entity, &Position), With<EncounterSpawner>>,
+    goose_query: Query<&Goose, Without<Expired>>,
 ) {
-    trace!("spawn_geese");
+    trace!("spawn_geese_over_time");
+
+    population.timer.tick(Duration::from_millis(FIXED_TICK_MS));
+    let first = population.next_id == 0;
+    if !first && !population.timer.just_finished() {
+        return;
+    }
+    if goose_query.iter().len() >= population.max {
+        return;
+    }
 
-    let spawn_area: Vec<&Transform> = spawner_query.iter().collect();
+    let mut spawn_area = spawner_query.iter().collect::<Vec<_>>();
+    if spawn_area.is_empty() {
+        return;
+    }
+    spawn_area.sort_by_key(|e| e.0);
     let idx = agreed_seed.rng.gen_range(0..spawn_area.len());
-    let pos = spawn_area[idx].translation;
+    let pos = spawn_area[idx].1 .0;
 
     let mut sprite = TextureAtlasSprite::new(characters.goose_frames[0]);
     sprite.custom_size = Some(Vec2::splat(TILE_SIZE * 2.));
 
+    let id = population.next_id;
+    population.next_id += 1;
+    debug!("spawning goose {} at {:?}", id, pos);
+
     commands
         .spawn((
-            Name::new("Goose"),
+            Name::new(format!("Goose {}", id)),
             SpriteSheetBundle {
                 sprite,
                 texture_atlas: characters.goose_handle.clone(),
-                transform: Transform {
-                    translation: Vec3::new(pos.x, pos.y, 1.),
-                    ..Default::default()
-                },
+                transform: Transform::from_translation(pos.extend(1.)),
                 ..Default::default()
             },
+            Position(pos),
             FrameAnimation {
                 timer: Timer::from_seconds(0.2, TimerMode::Repeating),
                 frames: characters.goose_frames.to_vec(),
                 current_frame: 0,
             },
-            Goose,
+            Goose { id },
+            GooseHealth::default(),
+            GooseBrain {
+                nest: pos,
+                wander_seed: agreed_seed.rng.gen(),
+                ..Default::default()
+            },
             RoundComponent,
         ))
         .add_rollback();
 }
 
-pub fn geese_target_closest_edible(
+/// Applies the hits geese took this frame in the order they were sent. A goose that runs out
+/// of health is credited to whoever landed the last hit and drops a random edible.
+pub fn damage_geese(
     mut commands: Commands,
-    edible_query: Query<(Entity, &Transform), (With<Edible>, Without<Expired>)>,
-    goose_query: Query<(Entity, &Transform), (With<Goose>, Without<HasTarget>)>,
+    registry: Res<EdibleRegistry>,
+    mut agreed_seed: ResMut<AgreedRandom>,
+    mut stats: ResMut<RoundStats>,
+    mut hit_events: EventReader<GooseHitEvent>,
+    target_query: Query<(Entity, &EdibleTarget)>,
+    mut goose_query: Query<(&Goose, &Position, &mut GooseHealth), Without<Expired>>,
 ) {
-    trace!("geese_target_closest_edible");
+    trace!("damage_geese");
+
+    for hit in hit_events.read() {
+        let Ok((goose, position, mut health)) = goose_query.get_mut(hit.goose) else {
+            continue;
+        };
+        if health.0 <= 0 {
+            continue; // already killed by an earlier hit this frame
+        }
+        health.0 -= hit.amount;
+        commands.entity(hit.goose).insert(GooseHitFlash::default());
+        if health.0 > 0 {
+            continue;
+        }
 
-    for (goose_entity, transform) in goose_query.iter() {
-        let mut closest_entity = Entity::PLACEHOLDER;
-        let mut closest_transform = Vec3::splat(0.);
-        let goose_pos = transform.translation;
-
-        // find closest edible entity
-        for (edible_entity, edible_transform) in edible_query.iter() {
-            // replace placeholder values on first iteration to avoid failing to
-            // find a target in certain locations
-            if closest_entity == Entity::PLACEHOLDER && edible_entity != Entity::PLACEHOLDER {
-                closest_entity = edible_entity;
-                closest_transform = edible_transform.translation;
+        debug!("goose {} killed by player {}", goose.id, hit.attacker);
+        commands.entity(hit.goose).insert(Expired);
+        stats.record_goose_kill(hit.attacker);
+        for (target, claim) in target_query.iter() {
+            if claim.claimed_by == goose.id {
+                commands.entity(target).remove::<EdibleTarget>();
             }
+        }
+
+        if registry.edibles.is_empty() {
+            continue;
+        }
+        let kind = agreed_seed.rng.gen_range(0..registry.edibles.len());
+        spawn_edible(&mut commands, kind, &registry.edibles[kind], position.0);
+    }
+}
+
+pub fn tick_goose_hit_flash(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut GooseHitFlash)>,
+) {
+    let mut geese = query.iter_mut().collect::<Vec<_>>();
+    geese.sort_by_key(|e| e.0);
+
+    for (entity, mut flash) in geese {
+        flash.timer.tick(Duration::from_millis(FIXED_TICK_MS));
+        if flash.timer.finished() {
+            commands.entity(entity).remove::<GooseHitFlash>();
+        }
+    }
+}
+
+/// Tints geese that were just hit, this is visual only
+pub fn flash_hit_geese(
+    mut query: Query<(&mut TextureAtlasSprite, Option<&GooseHitFlash>), With<Goose>>,
+) {
+    for (mut sprite, flash) in query.iter_mut() {
+        let color = match flash {
+            Some(_) => Color::rgb(1., 0.3, 0.3),
+            None => Color::WHITE,
+        };
+        if sprite.color != color {
+            sprite.color = color;
+        }
+    }
+}
 
-            let distance_to_next = goose_pos.distance(edible_transform.translation);
-            let distance_to_previous = goose_pos.distance(closest_transform);
+/// What a goose knows about its surroundings when picking a state
+struct GooseSenses {
+    hit_by: Option<usize>,
+    /// Handle and distance of the closest visible turtle
+    nearest_turtle: Option<(usize, Fixed)>,
+    /// Distance to the chased turtle while it is still visible
+    chased_distance: Option<Fixed>,
+    nest_distance: Fixed,
+    edible_in_sight: bool,
+}
 
-            if distance_to_next < distance_to_previous {
-                closest_entity = edible_entity;
-                closest_transform = edible_transform.translation;
+/// Behaviours from the most to the least urgent. Chasing a turtle and returning to the nest
+/// run until they are done, a goose that gave up on a turtle heads home before anything else.
+fn decide(brain: &GooseBrain, senses: &GooseSenses) -> GooseState {
+    if let Some(attacker) = senses.hit_by {
+        return GooseState::Flee(attacker);
+    }
+    match brain.state {
+        GooseState::Flee(handle) if brain.frames_in_state < GOOSE_FLEE_FRAMES => {
+            return GooseState::Flee(handle);
+        }
+        GooseState::ChaseTurtle(handle) => {
+            let in_range = senses
+                .chased_distance
+                .is_some_and(|d| d < GOOSE_CHASE_RANGE);
+            if in_range && brain.frames_in_state < GOOSE_CHASE_FRAMES {
+                return GooseState::ChaseTurtle(handle);
             }
+            return GooseState::ReturnToNest;
         }
+        GooseState::ReturnToNest if senses.nest_distance > GOOSE_NEST_RANGE => {
+            return GooseState::ReturnToNest;
+        }
+        _ => {}
+    }
+
+    if let Some((handle, distance)) = senses.nearest_turtle {
+        if distance < GOOSE_AGGRO_RANGE {
+            return GooseState::ChaseTurtle(handle);
+        }
+    }
+    if senses.nest_distance > GOOSE_LEASH_RANGE {
+        return GooseState::ReturnToNest;
+    }
+    if senses.edible_in_sight {
+        return GooseState::ChaseEdible;
+    }
+    GooseState::Wander
+}
+
+/// Picks the state of every goose for this frame, a goose honks when it starts chasing a turtle
+#[allow(clippy::too_many_arguments)]
+pub fn geese_think(
+    mut commands: Commands,
+    frame: Res<FrameCount>,
+    sounds: Res<AudioAssets>,
+    mut hit_events: EventReader<GooseHitEvent>,
+    player_query: Query<(&Position, &Player, &StatusEffects), Without<Goose>>,
+    edible_query: Query<&Position, VisibleEdible>,
+    target_query: Query<(Entity, &EdibleTarget)>,
+    mut goose_query: Query<(Entity, &Goose, &Position, &mut GooseBrain), Without<Expired>>,
+) {
+    trace!("geese_think");
+
+    let hits = hit_events.read().copied().collect::<Vec<_>>();
+
+    // invisible and dead turtles can't be seen
+    let mut turtles = player_query
+        .iter()
+        .filter(|(_, p, effects)| p.active && !effects.has(StatusKind::Invisibility))
+        .map(|(pos, p, _)| (p.handle, pos.0))
+        .collect::<Vec<_>>();
+    turtles.sort_by_key(|t| t.0);
+
+    let edibles = edible_query.iter().map(|p| p.0).collect::<Vec<_>>();
 
-        if closest_entity == Entity::PLACEHOLDER {
-            // do not target placeholder entities
-            // they may be outside of the map
-            debug!("skip target placeholder entity");
+    let mut geese = goose_query.iter_mut().collect::<Vec<_>>();
+    geese.sort_by_key(|g| g.0);
+
+    for (goose_entity, goose, position, mut brain) in geese {
+        let pos = position.0;
+        let chased = match brain.state {
+            GooseState::ChaseTurtle(handle) => Some(handle),
+            _ => None,
+        };
+        let senses = GooseSenses {
+            hit_by: hits
+                .iter()
+                .find(|h| h.goose == goose_entity)
+                .map(|h| h.attacker),
+            // ties go to the lowest handle
+            nearest_turtle: turtles
+                .iter()
+                .map(|(handle, p)| (*handle, p.distance(pos)))
+                .min_by_key(|t| t.1),
+            chased_distance: turtles
+                .iter()
+                .find(|t| Some(t.0) == chased)
+                .map(|t| t.1.distance(pos)),
+            nest_distance: brain.nest.distance(pos),
+            edible_in_sight: edibles.iter().any(|e| e.distance(pos) < GOOSE_SIGHT_RANGE),
+        };
+
+        let previous = brain.state;
+        let next = decide(&brain, &senses);
+        brain.set_state(next);
+        brain.frames_in_state = brain.frames_in_state.saturating_add(1);
+        if brain.state == previous {
             continue;
         }
+        debug!(
+            "goose {:?} {:?} -> {:?}",
+            goose_entity, previous, brain.state
+        );
+
+        if previous == GooseState::ChaseEdible {
+            // let go of the food so it can be targeted again later
+            commands.entity(goose_entity).remove::<HasTarget>();
+            for (target, claim) in target_query.iter() {
+                if claim.claimed_by == goose.id {
+                    commands.entity(target).remove::<EdibleTarget>();
+                }
+            }
+        }
+        if let GooseState::ChaseTurtle(_) = brain.state {
+            commands
+                .spawn(RollbackSoundBundle {
+                    sound: RollbackSound {
+                        clip: sounds.goose_honk.clone(),
+                        start_frame: frame.0,
+                        sub_key: goose_entity.index(),
+                    },
+                })
+                .add_rollback();
+        }
+    }
+}
+
+fn wander_dir(seed: u32, leg: u32) -> FixedVec2 {
+    let hash = (seed ^ leg.wrapping_mul(0x9e37_79b9)).wrapping_mul(0x85eb_ca6b);
+    WANDER_DIRS[(hash >> 29) as usize]
+}
+
+/// Moves a goose one axis at a time so it slides along walls, returns true when a wall
+/// stopped it
+fn step_goose(
+    position: &mut FixedVec2,
+    sprite: &mut TextureAtlasSprite,
+    movement: FixedVec2,
+    walls: &[FixedVec2],
+) -> bool {
+    let mut blocked = false;
+    for axis in [
+        FixedVec2::new(movement.x, Fixed::ZERO),
+        FixedVec2::new(Fixed::ZERO, movement.y),
+    ] {
+        let target = *position + axis;
+        if walls.iter().any(|wall| wall_collision_check(target, *wall)) {
+            blocked = true;
+        } else {
+            *position = target;
+        }
+    }
+    if movement.x != Fixed::ZERO {
+        sprite.flip_x = movement.x < Fixed::ZERO;
+    }
+    blocked
+}
+
+/// Walks every goose that isn't after food according to its state, a wandering goose turns
+/// when it bumps into a wall
+pub fn move_geese(
+    player_query: Query<(&Position, &Player), Without<Goose>>,
+    wall_query: Query<&Position, (With<TileCollider>, Without<Goose>)>,
+    mut goose_query: Query<
+        (
+            Entity,
+            &mut Position,
+            &mut GooseBrain,
+            &mut TextureAtlasSprite,
+        ),
+        With<Goose>,
+    >,
+) {
+    trace!("move_geese");
+
+    let walls = wall_query.iter().map(|w| w.0).collect::<Vec<_>>();
+    let turtle_pos = |handle: usize| {
+        player_query
+            .iter()
+            .find(|(_, p)| p.handle == handle)
+            .map(|(pos, _)| pos.0)
+    };
+
+    let mut geese = goose_query.iter_mut().collect::<Vec<_>>();
+    geese.sort_by_key(|g| g.0);
+
+    for (_, mut position, mut brain, mut sprite) in geese {
+        let pos = position.0;
+        let (dir, speed) = match brain.state {
+            GooseState::ChaseEdible => continue, // see move_geese_toward_target
+            GooseState::Wander => {
+                if brain.frames_in_state % GOOSE_WANDER_LEG_FRAMES == 0 {
+                    brain.wander_leg += 1;
+                }
+                (
+                    wander_dir(brain.wander_seed, brain.wander_leg),
+                    GOOSE_WANDER_SPEED,
+                )
+            }
+            GooseState::ChaseTurtle(handle) => match turtle_pos(handle) {
+                Some(target) => ((target - pos).normalize_or_zero(), GOOSE_CHASE_SPEED),
+                None => continue,
+            },
+            GooseState::Flee(handle) => match turtle_pos(handle) {
+                Some(threat) => ((pos - threat).normalize_or_zero(), GOOSE_FLEE_SPEED),
+                None => continue,
+            },
+            GooseState::ReturnToNest => ((brain.nest - pos).normalize_or_zero(), GOOSE_SPEED),
+        };
+
+        let blocked = step_goose(
+            &mut position.0,
+            &mut sprite,
+            dir * speed / FPS as i32,
+            &walls,
+        );
+        if blocked && brain.state == GooseState::Wander {
+            brain.wander_leg += 1;
+        }
+    }
+}
+
+/// Every goose after food claims the closest edible no other goose has claimed yet, geese
+/// pick in spawn order and ties go to the first edible in entity order
+pub fn geese_target_closest_edible(
+    mut commands: Commands,
+    edible_query: Query<(Entity, &Position), UnclaimedEdible>,
+    goose_query: Query<(Entity, &Goose, &Position, &GooseBrain), Without<HasTarget>>,
+) {
+    trace!("geese_target_closest_edible");
+
+    let mut edibles = edible_query.iter().collect::<Vec<_>>();
+    edibles.sort_by_key(|e| e.0);
+
+    let mut geese = goose_query.iter().collect::<Vec<_>>();
+    geese.sort_by_key(|g| g.1.id);
+
+    for (goose_entity, goose, position, brain) in geese {
+        if brain.state != GooseState::ChaseEdible {
+            continue;
+        }
+        let goose_pos = position.0;
+        let Some(index) = edibles
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, (_, edible_pos))| goose_pos.distance(edible_pos.0))
+            .map(|(index, _)| index)
+        else {
+            continue; // everything is claimed already
+        };
+        // claimed edibles are left to the geese before us
+        let (edible_entity, edible_pos) = edibles.remove(index);
 
         debug!(
-            "goose targeting closest entity {:?} at distance {:?}",
-            closest_entity,
-            goose_pos.distance(closest_transform)
+            "goose {} targeting closest entity {:?} at distance {:?}",
+            goose.id,
+            edible_entity,
+            goose_pos.distance(edible_pos.0)
         );
-        commands.entity(closest_entity).insert(EdibleTarget);
+        commands.entity(edible_entity).insert(EdibleTarget {
+            claimed_by: goose.id,
+        });
         commands.entity(goose_entity).insert(HasTarget);
     }
 }
 
+/// Walks every goose towards the edible it claimed, a goose whose edible is gone looks for a
+/// new one next frame
 pub fn move_geese_toward_target(
     mut commands: Commands,
-    target_query: Query<(Entity, &Transform), (With<EdibleTarget>, Without<Expired>)>,
-    mut goose_query: Query<
-        (Entity, &mut Transform, &mut TextureAtlasSprite),
-        (With<Goose>, With<HasTarget>, Without<EdibleTarget>),
-    >,
+    target_query: Query<(&Position, &EdibleTarget), ClaimedEdible>,
+    wall_query: Query<&Position, (With<TileCollider>, Without<Goose>)>,
+    mut goose_query: Query<ChasingGoose, With<HasTarget>>,
 ) {
     trace!("move_geese_toward_target");
 
-    // collect and sort all targets in play so we move towards them in a deterministic order
-    let mut targets = target_query.iter().collect::<Vec<_>>();
-    targets.sort_by_key(|e| e.0);
-    debug!("number of targets: {:?}", targets.len());
+    let walls = wall_query.iter().map(|w| w.0).collect::<Vec<_>>();
 
-    for (goose_entity, mut goose_pos, mut sprite) in goose_query.iter_mut() {
-        debug!("goose loop");
-        if targets.len() == 0 {
-            commands.entity(goose_entity).remove::<HasTarget>();
-            return;
-        }
-        let target = &targets[0]; // move towards first target
+    // collect and sort all geese so we move them in a deterministic order
+    let mut geese = goose_query.iter_mut().collect::<Vec<_>>();
+    geese.sort_by_key(|g| g.0);
 
-        let dir = (goose_pos.translation.xy() - target.1.translation.xy()).normalize();
-        let movement = (dir * GOOSE_SPEED as f32 / FPS as f32).extend(0.);
-        if movement.x != 0.0 {
-            if movement.x > 0.0 {
-                sprite.flip_x = true;
-            } else {
-                sprite.flip_x = false;
-            }
+    for (goose_entity, goose, mut goose_pos, mut sprite, brain) in geese {
+        if brain.state != GooseState::ChaseEdible {
+            continue;
         }
-        let target = goose_pos.translation - Vec3::new(movement.x, movement.y, 0.0);
-        goose_pos.translation = target; // TODO: prevent goose from clipping through walls
+        let target = target_query
+            .iter()
+            .find(|(_, claim)| claim.claimed_by == goose.id);
+        let Some((target_pos, _)) = target else {
+            // don't return, the other geese still have to move
+            commands.entity(goose_entity).remove::<HasTarget>();
+            continue;
+        };
+
+        let dir = (target_pos.0 - goose_pos.0).normalize_or_zero();
+        step_goose(
+            &mut goose_pos.0,
+            &mut sprite,
+            dir * GOOSE_SPEED / FPS as i32,
+            &walls,
+        );
     }
 }
 
+/// A goose that reached the edible it claimed eats it
 pub fn goose_ate_edible(
     mut commands: Commands,
-    goose_query: Query<(Entity, &Transform), (With<Goose>, With<HasTarget>)>,
-    target_query: Query<(Entity, &Transform), (With<EdibleTarget>, Without<Expired>)>,
+    goose_query: Query<(Entity, &Goose, &Position), With<HasTarget>>,
+    target_query: Query<(Entity, &Position, &EdibleTarget), Without<Expired>>,
 ) {
     trace!("goose_ate_edible");
 
-    // collect and sort all fireballs in play so we despawn them in a deterministic order
-    let mut targets = target_query.iter().collect::<Vec<_>>();
-    targets.sort_by_key(|e| e.0);
-
-    for (goose_entity, goose_pos) in goose_query.iter() {
-        for target in &targets {
-            let distance = goose_pos.translation.distance(target.1.translation);
-            debug!(
-                "goose distance from target: {:?} - {:?}",
-                target.0, distance
-            );
+    // collect and sort all geese in play so we despawn edibles in a deterministic order
+    let mut geese = goose_query.iter().collect::<Vec<_>>();
+    geese.sort_by_key(|g| g.0);
 
-            if distance < TILE_SIZE / 2. {
-                debug!("goose reached target, despawn and find new one");
+    for (goose_entity, goose, goose_pos) in geese {
+        for (target, target_pos, claim) in target_query.iter() {
+            if claim.claimed_by != goose.id {
+                continue;
+            }
+            let distance = goose_pos.0.distance(target_pos.0);
+            if distance < Fixed::from_f32(TILE_SIZE / 2.) {
+                debug!(
+                    "goose {} reached target, despawn and find new one",
+                    goose.id
+                );
                 commands.entity(goose_entity).remove::<HasTarget>();
-                commands.entity(target.0).insert(Expired);
+                commands.entity(target).insert(Expired);
             }
         }
     }
@@ -0,0 +1,381 @@
+use bevy::prelude::*;
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+
+const FRAC_BITS: u32 = 16;
+const ONE_BITS: i32 = 1 << FRAC_BITS;
+
+pub struct PhysicsPlugin;
+
+/// Floats can round differently between the native and the wasm build, so everything the
+/// rollback simulation carries from one frame to the next is kept in fixed-point numbers.
+/// `Transform` only follows the simulation for rendering.
+impl Plugin for PhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, sync_transforms.in_set(SyncTransformsSet));
+    }
+}
+
+/// Transforms are up to date after this set
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
+pub struct SyncTransformsSet;
+
+/// Fixed-point number with 16 fractional bits, enough for positions across the whole map
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord, Reflect)]
+pub struct Fixed(i32);
+
+impl Fixed {
+    pub const ZERO: Fixed = Fixed(0);
+    pub const ONE: Fixed = Fixed(ONE_BITS);
+    pub const MAX: Fixed = Fixed(i32::MAX);
+
+    pub const fn from_bits(bits: i32) -> Self {
+        Fixed(bits)
+    }
+
+    pub const fn to_bits(self) -> i32 {
+        self.0
+    }
+
+    pub const fn from_int(value: i32) -> Self {
+        Fixed(value << FRAC_BITS)
+    }
+
+    /// The conversion itself is exact on every platform, only use it for constants and values
+    /// all peers share, never for the result of float math
+    pub const fn from_f32(value: f32) -> Self {
+        Fixed((value * ONE_BITS as f32) as i32)
+    }
+
+    /// Rounds toward zero, panics when `den` is zero
+    pub const fn from_ratio(num: i32, den: i32) -> Self {
+        Fixed((((num as i64) << FRAC_BITS) / den as i64) as i32)
+    }
+
+    pub fn to_f32(self) -> f32 {
+        self.0 as f32 / ONE_BITS as f32
+    }
+
+    pub fn abs(self) -> Self {
+        Fixed(self.0.abs())
+    }
+}
+
+impl Add for Fixed {
+    type Output = Fixed;
+    fn add(self, rhs: Fixed) -> Fixed {
+        Fixed(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Fixed {
+    fn add_assign(&mut self, rhs: Fixed) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Fixed;
+    fn sub(self, rhs: Fixed) -> Fixed {
+        Fixed(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for Fixed {
+    fn sub_assign(&mut self, rhs: Fixed) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Neg for Fixed {
+    type Output = Fixed;
+    fn neg(self) -> Fixed {
+        Fixed(-self.0)
+    }
+}
+
+impl Mul for Fixed {
+    type Output = Fixed;
+    fn mul(self, rhs: Fixed) -> Fixed {
+        Fixed(((self.0 as i64 * rhs.0 as i64) >> FRAC_BITS) as i32)
+    }
+}
+
+impl MulAssign for Fixed {
+    fn mul_assign(&mut self, rhs: Fixed) {
+        *self = *self * rhs;
+    }
+}
+
+impl Mul<i32> for Fixed {
+    type Output = Fixed;
+    fn mul(self, rhs: i32) -> Fixed {
+        Fixed(self.0 * rhs)
+    }
+}
+
+/// Rounds toward zero and panics on a zero divisor like integer division, callers check
+/// for zero first, see `FixedVec2::normalize_or_zero`
+impl Div for Fixed {
+    type Output = Fixed;
+    fn div(self, rhs: Fixed) -> Fixed {
+        Fixed((((self.0 as i64) << FRAC_BITS) / rhs.0 as i64) as i32)
+    }
+}
+
+impl Div<i32> for Fixed {
+    type Output = Fixed;
+    fn div(self, rhs: i32) -> Fixed {
+        Fixed(self.0 / rhs)
+    }
+}
+
+/// Integer square root, rounded down
+fn isqrt(n: u64) -> u64 {
+    let mut rest = n;
+    let mut root = 0;
+    let mut bit = 1u64 << 62;
+    while bit > rest {
+        bit >>= 2;
+    }
+    while bit != 0 {
+        if rest >= root + bit {
+            rest -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
+    }
+    root
+}
+
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, Reflect)]
+pub struct FixedVec2 {
+    pub x: Fixed,
+    pub y: Fixed,
+}
+
+impl FixedVec2 {
+    pub const ZERO: FixedVec2 = FixedVec2::new(Fixed::ZERO, Fixed::ZERO);
+
+    pub const fn new(x: Fixed, y: Fixed) -> Self {
+        FixedVec2 { x, y }
+    }
+
+    pub const fn splat(value: Fixed) -> Self {
+        FixedVec2::new(value, value)
+    }
+
+    /// See `Fixed::from_f32`, player inputs and map data are safe to convert
+    pub const fn from_vec2(v: Vec2) -> Self {
+        FixedVec2::new(Fixed::from_f32(v.x), Fixed::from_f32(v.y))
+    }
+
+    pub fn to_vec2(self) -> Vec2 {
+        Vec2::new(self.x.to_f32(), self.y.to_f32())
+    }
+
+    pub fn extend(self, z: f32) -> Vec3 {
+        self.to_vec2().extend(z)
+    }
+
+    pub fn length(self) -> Fixed {
+        // squared in 64 bits, positions on the map overflow 32
+        let x = self.x.0 as i64;
+        let y = self.y.0 as i64;
+        Fixed(isqrt((x * x + y * y) as u64) as i32)
+    }
+
+    pub fn distance(self, other: FixedVec2) -> Fixed {
+        (self - other).length()
+    }
+
+    pub fn normalize_or_zero(self) -> Self {
+        let length = self.length();
+        if length == Fixed::ZERO {
+            return FixedVec2::ZERO;
+        }
+        FixedVec2::new(self.x / length, self.y / length)
+    }
+
+    pub fn dot(self, other: FixedVec2) -> Fixed {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// Positive when `other` is counterclockwise from `self`
+    pub fn perp_dot(self, other: FixedVec2) -> Fixed {
+        self.x * other.y - self.y * other.x
+    }
+
+    /// Rotates counterclockwise by the angle with this cosine and sine, precompute them since
+    /// trigonometry is not deterministic across platforms
+    pub fn rotate(self, cos: Fixed, sin: Fixed) -> Self {
+        FixedVec2::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
+}
+
+impl Add for FixedVec2 {
+    type Output = FixedVec2;
+    fn add(self, rhs: FixedVec2) -> FixedVec2 {
+        FixedVec2::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl AddAssign for FixedVec2 {
+    fn add_assign(&mut self, rhs: FixedVec2) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for FixedVec2 {
+    type Output = FixedVec2;
+    fn sub(self, rhs: FixedVec2) -> FixedVec2 {
+        FixedVec2::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl SubAssign for FixedVec2 {
+    fn sub_assign(&mut self, rhs: FixedVec2) {
+        *self = *self - rhs;
+    }
+}
+
+impl Neg for FixedVec2 {
+    type Output = FixedVec2;
+    fn neg(self) -> FixedVec2 {
+        FixedVec2::new(-self.x, -self.y)
+    }
+}
+
+impl Mul<Fixed> for FixedVec2 {
+    type Output = FixedVec2;
+    fn mul(self, rhs: Fixed) -> FixedVec2 {
+        FixedVec2::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl MulAssign<Fixed> for FixedVec2 {
+    fn mul_assign(&mut self, rhs: Fixed) {
+        *self = *self * rhs;
+    }
+}
+
+impl Mul<i32> for FixedVec2 {
+    type Output = FixedVec2;
+    fn mul(self, rhs: i32) -> FixedVec2 {
+        FixedVec2::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl Div<Fixed> for FixedVec2 {
+    type Output = FixedVec2;
+    fn div(self, rhs: Fixed) -> FixedVec2 {
+        FixedVec2::new(self.x / rhs, self.y / rhs)
+    }
+}
+
+impl Div<i32> for FixedVec2 {
+    type Output = FixedVec2;
+    fn div(self, rhs: i32) -> FixedVec2 {
+        FixedVec2::new(self.x / rhs, self.y / rhs)
+    }
+}
+
+/// Where an entity is in the simulation, its `Transform` follows
+#[derive(Clone, Copy, Component, Debug, Default, Hash, PartialEq, Eq, Reflect)]
+#[reflect(Component, Hash)]
+pub struct Position(pub FixedVec2);
+
+/// Moves transforms to their simulated position, each transform keeps its z for draw order
+pub fn sync_transforms(mut query: Query<(&Position, &mut Transform), Changed<Position>>) {
+    for (position, mut transform) in query.iter_mut() {
+        transform.translation = position.0.extend(transform.translation.z);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vec(x: i32, y: i32) -> FixedVec2 {
+        FixedVec2::new(Fixed::from_int(x), Fixed::from_int(y))
+    }
+
+    #[test]
+    fn mul_and_div() {
+        let half = Fixed::from_ratio(1, 2);
+        assert_eq!(Fixed::from_int(3) * half, Fixed::from_ratio(3, 2));
+        assert_eq!(
+            Fixed::from_int(3) / Fixed::from_int(2),
+            Fixed::from_ratio(3, 2)
+        );
+        assert_eq!(Fixed::from_int(3) * 2, Fixed::from_int(6));
+        assert_eq!(Fixed::from_int(3) / 2, Fixed::from_ratio(3, 2));
+        assert_eq!(half.to_f32(), 0.5);
+    }
+
+    #[test]
+    fn negative_values() {
+        assert_eq!(
+            Fixed::from_int(-3) * Fixed::from_int(2),
+            Fixed::from_int(-6)
+        );
+        assert_eq!(
+            Fixed::from_int(-3) / Fixed::from_int(2),
+            Fixed::from_ratio(-3, 2)
+        );
+        assert_eq!(Fixed::from_ratio(-1, 2).to_f32(), -0.5);
+        assert_eq!(Fixed::from_ratio(-1, 3), -Fixed::from_ratio(1, 3));
+        assert_eq!(Fixed::from_int(-2).abs(), Fixed::from_int(2));
+        assert_eq!(vec(-3, -4).length(), Fixed::from_int(5));
+    }
+
+    #[test]
+    fn from_ratio_rounds_toward_zero() {
+        assert_eq!(Fixed::from_ratio(1, 3).to_bits(), ONE_BITS / 3);
+        assert_eq!(Fixed::from_ratio(-1, 3).to_bits(), -ONE_BITS / 3);
+        assert_eq!(Fixed::from_ratio(4, 2), Fixed::from_int(2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn div_by_zero_panics() {
+        let _ = Fixed::ONE / Fixed::ZERO;
+    }
+
+    #[test]
+    fn isqrt_rounds_down() {
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(1), 1);
+        assert_eq!(isqrt(15), 3);
+        assert_eq!(isqrt(16), 4);
+        assert_eq!(isqrt(u64::MAX), u32::MAX as u64);
+    }
+
+    #[test]
+    fn distance() {
+        assert_eq!(vec(1, 2).distance(vec(4, 6)), Fixed::from_int(5));
+        assert_eq!(vec(4, 6).distance(vec(1, 2)), Fixed::from_int(5));
+        // far apart positions don't overflow
+        assert_eq!(vec(-3000, 0).distance(vec(3000, 0)), Fixed::from_int(6000));
+    }
+
+    #[test]
+    fn normalize_or_zero() {
+        assert_eq!(
+            vec(3, 4).normalize_or_zero(),
+            FixedVec2::new(Fixed::from_ratio(3, 5), Fixed::from_ratio(4, 5))
+        );
+        assert_eq!(vec(0, -2).normalize_or_zero(), vec(0, -1));
+        assert_eq!(FixedVec2::ZERO.normalize_or_zero(), FixedVec2::ZERO);
+    }
+
+    #[test]
+    fn rotate() {
+        // a quarter turn counterclockwise
+        assert_eq!(vec(1, 0).rotate(Fixed::ZERO, Fixed::ONE), vec(0, 1));
+        assert_eq!(vec(0, 1).rotate(Fixed::ZERO, Fixed::ONE), vec(-1, 0));
+        // half a turn
+        assert_eq!(vec(2, -3).rotate(-Fixed::ONE, Fixed::ZERO), vec(-2, 3));
+    }
+}
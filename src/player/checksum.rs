@@ -2,6 +2,7 @@ use bevy::prelude::*;
 use bevy_ggrs::Rollback;
 
 use super::components::Player;
+use crate::physics::Position;
 
 #[derive(Clone, Default, Reflect, Hash, Component)]
 #[reflect(Hash)]
@@ -10,13 +11,12 @@ pub struct Checksum {
 }
 
 pub fn checksum_players(
-    mut query: Query<(&Transform, &mut Checksum), (With<Player>, With<Rollback>)>,
+    mut query: Query<(&Position, &mut Checksum), (With<Player>, With<Rollback>)>,
 ) {
-    for (t, mut checksum) in query.iter_mut() {
-        let mut bytes = Vec::with_capacity(20);
-        bytes.extend_from_slice(&t.translation.x.to_le_bytes());
-        bytes.extend_from_slice(&t.translation.y.to_le_bytes());
-        bytes.extend_from_slice(&t.translation.z.to_le_bytes());
+    for (p, mut checksum) in query.iter_mut() {
+        let mut bytes = Vec::with_capacity(8);
+        bytes.extend_from_slice(&p.0.x.to_bits().to_le_bytes());
+        bytes.extend_from_slice(&p.0.y.to_bits().to_le_bytes());
 
         // naive checksum implementation
         checksum.value = fletcher16(&bytes);
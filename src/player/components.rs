@@ -3,94 +3,88 @@ use bevy_inspector_egui::prelude::*;
 use bevy_inspector_egui::InspectorOptions;
 use percentage::Percentage;
 
-pub const CHILI_PEPPER_SIZE: f32 = 20.0;
-pub const CHILI_PEPPER_AMMO_COUNT: i32 = 5;
-const CHILI_PEPPER_SPAWN_RATE: f32 = 2.5;
-
-pub const FIREBALL_RADIUS: f32 = 12.0;
-pub const FIREBALL_DAMAGE: i32 = 5;
-pub const FIREBALL_LIFETIME: f32 = 10.0;
-
-pub const STRAWBERRY_SIZE: f32 = 32.0;
-pub const STRAWBERRY_AMMO_COUNT: i32 = 5;
-const STRAWBERRY_SPAWN_RATE: f32 = 3.5;
-
-pub const LETTUCE_SIZE: f32 = 32.0;
-pub const LETTUCE_HEALTH_GAIN: i32 = 10;
-const LETTUCE_SPAWN_RATE: f32 = 5.;
+use super::edibles::EdibleRegistry;
+use super::resources::PlayerNames;
+use crate::physics::{Fixed, FixedVec2};
+use crate::{FPS, TILE_SIZE};
+
+/// Cosine and sine of the 0.26 radians between the fireballs of a triple shot
+pub const FIREBALL_SPREAD_COS: Fixed = Fixed::from_f32(0.966_39);
+pub const FIREBALL_SPREAD_SIN: Fixed = Fixed::from_f32(0.257_081);
+/// Cosine and sine of the most a homing fireball turns in one frame, 2 radians per second
+pub const FIREBALL_HOMING_TURN_COS: Fixed = Fixed::from_f32(0.999_444_5);
+pub const FIREBALL_HOMING_TURN_SIN: Fixed = Fixed::from_f32(0.033_327_16);
+/// Homing fireballs ignore players further away than this
+pub const FIREBALL_HOMING_RANGE: Fixed = Fixed::from_int(400);
 
 pub const PLAYER_HEALTH_MAX: i32 = 100;
 pub const PLAYER_HEALTH_MID: i32 = PLAYER_HEALTH_MAX / 2;
 pub const PLAYER_HEALTH_LOW: i32 = PLAYER_HEALTH_MAX / 4;
 
+pub const PLAYER_LIVES_START: i32 = 3;
+pub const PLAYER_RESPAWN_DELAY: f32 = 3.0;
+pub const PLAYER_INVULNERABLE_TIME: f32 = 2.0;
+
 pub const PLAYER_SPEED_START: i32 = 100;
 pub const PLAYER_SPEED_BOOST: i32 = 25;
 pub const PLAYER_SPEED_BOOST_MAX: i32 = 25;
 pub const PLAYER_SPEED_MAX: i32 = 800;
 
+pub const BASH_RANGE: Fixed = Fixed::from_f32(TILE_SIZE * 1.5);
+pub const BASH_DAMAGE: i32 = 8;
+pub const BASH_COOLDOWN: f32 = 1.0;
+/// Speed a bashed player is pushed away with, in units per second
+pub const BASH_KNOCKBACK: Fixed = Fixed::from_int(600);
+/// Share of the knockback velocity kept every frame
+pub const KNOCKBACK_DECAY: Fixed = Fixed::from_ratio(85, 100);
+/// Slower knockback stops completely
+pub const KNOCKBACK_MIN_SPEED: Fixed = Fixed::from_int(5);
+
 pub const POOP_SIZE: f32 = 16.0;
 pub const POOP_DAMAGE: i32 = 5;
 pub const POOP_LIFETIME: f32 = 15.0;
-pub const POOP_ENTITIES_MAX: usize = 20;
-
+pub const POOP_AMMO_START: i32 = 3;
+/// Most poops of one player on the map at once
+pub const POOP_PER_PLAYER_MAX: usize = 3;
+/// How far behind the turtle poop lands
+pub const POOP_DROP_DISTANCE: f32 = TILE_SIZE;
+/// Seconds a player who stepped in poop is slowed
+pub const POOP_SLIP_TIME: f32 = 2.0;
+
+pub const KILL_FEED_LIFETIME: i32 = 5 * FPS as i32;
+pub const KILL_FEED_MAX_LINES: usize = 5;
+
+/// Index of the edible's definition in the `EdibleRegistry`
 #[derive(Clone, Component, Default, Hash, Reflect)]
 #[reflect(Component, Hash)]
-pub enum Edible {
-    #[default]
-    PLACEHOLDER,
-    ChiliPepper,
-    Strawberry,
-    Lettuce,
-}
+pub struct Edible(pub usize);
 
 #[derive(Clone, Component, Default, Hash, Reflect)]
 #[reflect(Component, Hash)]
 pub struct RoundComponent;
 
-#[derive(Clone, Component, Default, Hash, Reflect)]
-#[reflect(Component, Hash)]
-pub struct Fireball {
-    pub shot_by: usize,
-}
-
-#[derive(Clone, Component, Default, Reflect)]
-#[reflect(Component)]
-pub struct FireballMovement {
-    pub dir: Vec2,
-    pub speed: f32,
-}
-
-#[derive(Clone, Component, Reflect)]
-#[reflect(Component)]
-pub struct FireballTimer {
-    pub lifetime: Timer,
-}
-
-impl Default for FireballTimer {
-    fn default() -> Self {
-        FireballTimer {
-            lifetime: Timer::from_seconds(FIREBALL_LIFETIME, TimerMode::Once),
-        }
-    }
-}
-
-#[derive(Clone, Resource, Reflect)]
+/// One repeating spawn timer for every edible in the `EdibleRegistry`
+#[derive(Clone, Default, Resource, Reflect)]
 #[reflect(Resource)]
 pub struct EdibleSpawnTimer {
     // TODO: impl hash for sync test?
-    pub chili_pepper_timer: Timer,
-    pub strawberry_timer: Timer,
-    pub lettuce_timer: Timer,
+    pub timers: Vec<Timer>,
 }
 
-impl Default for EdibleSpawnTimer {
-    fn default() -> Self {
+impl EdibleSpawnTimer {
+    pub fn new(registry: &EdibleRegistry) -> EdibleSpawnTimer {
         EdibleSpawnTimer {
-            chili_pepper_timer: Timer::from_seconds(CHILI_PEPPER_SPAWN_RATE, TimerMode::Repeating),
-            strawberry_timer: Timer::from_seconds(STRAWBERRY_SPAWN_RATE, TimerMode::Repeating),
-            lettuce_timer: Timer::from_seconds(LETTUCE_SPAWN_RATE, TimerMode::Repeating),
+            timers: registry
+                .edibles
+                .iter()
+                .map(|def| Timer::from_seconds(def.spawn_rate, TimerMode::Repeating))
+                .collect(),
         }
     }
+
+    pub fn finished(&self, kind: usize) -> bool {
+        self.timers.get(kind).is_some_and(|t| t.finished())
+    }
 }
 
 #[derive(Clone, Component, Default, Reflect, Hash)]
@@ -113,6 +107,20 @@ impl Default for PlayerPoopTimer {
     }
 }
 
+#[derive(Clone, Component, Reflect)]
+#[reflect(Component)]
+pub struct PoopAmmo(pub i32);
+
+impl Default for PoopAmmo {
+    fn default() -> Self {
+        PoopAmmo(POOP_AMMO_START)
+    }
+}
+
+#[derive(Clone, Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct PoopReady(pub bool);
+
 #[derive(Clone, Component, Default, Reflect)]
 #[reflect(Component)]
 pub struct FireballAmmo(pub i32);
@@ -138,13 +146,66 @@ impl Default for PlayerSpeed {
     }
 }
 
-#[derive(Component, Copy, Clone, Debug, Reflect, InspectorOptions, Hash)]
+/// Velocity from impulses like a shell-bash knockback in units per second, added on top of
+/// the player's own movement and slowed down every frame
+#[derive(Clone, Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct PlayerVelocity(pub FixedVec2);
+
+/// Present while the player can't shell-bash again
+#[derive(Clone, Component, Reflect)]
+#[reflect(Component)]
+pub struct BashCooldown {
+    pub timer: Timer,
+}
+
+impl Default for BashCooldown {
+    fn default() -> Self {
+        BashCooldown {
+            timer: Timer::from_seconds(BASH_COOLDOWN, TimerMode::Once),
+        }
+    }
+}
+
+#[derive(Component, Copy, Clone, Debug, Default, Reflect, InspectorOptions, Hash)]
 #[reflect(Component, InspectorOptions, Hash)]
 pub struct PlayerSpeedBoost(pub i32);
 
-impl Default for PlayerSpeedBoost {
+#[derive(Component, Copy, Clone, Debug, Reflect, InspectorOptions, Hash)]
+#[reflect(Component, InspectorOptions, Hash)]
+pub struct PlayerLives(pub i32);
+
+impl Default for PlayerLives {
+    fn default() -> Self {
+        PlayerLives(PLAYER_LIVES_START)
+    }
+}
+
+#[derive(Clone, Component, Reflect)]
+#[reflect(Component)]
+pub struct PlayerRespawnTimer {
+    pub delay: Timer,
+}
+
+impl Default for PlayerRespawnTimer {
     fn default() -> Self {
-        PlayerSpeedBoost(0)
+        PlayerRespawnTimer {
+            delay: Timer::from_seconds(PLAYER_RESPAWN_DELAY, TimerMode::Once),
+        }
+    }
+}
+
+#[derive(Clone, Component, Reflect)]
+#[reflect(Component)]
+pub struct PlayerInvulnerable {
+    pub lifetime: Timer,
+}
+
+impl Default for PlayerInvulnerable {
+    fn default() -> Self {
+        PlayerInvulnerable {
+            lifetime: Timer::from_seconds(PLAYER_INVULNERABLE_TIME, TimerMode::Once),
+        }
     }
 }
 
@@ -172,7 +233,6 @@ impl PlayerHealth {
 
 #[derive(Clone, Component, Debug, Hash, Reflect)]
 #[reflect(Hash)]
-
 pub struct PlayerHealthBar {
     pub health_entity: Entity,
 }
@@ -185,15 +245,117 @@ impl Default for PlayerHealthBar {
     }
 }
 
+#[derive(Component)]
+pub struct PlayerNameTag;
+
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, Reflect)]
+pub enum DamageSource {
+    #[default]
+    Fireball,
+    Poop,
+    Disconnect,
+    Poison,
+    Burning,
+    Bash,
+}
+
+impl DamageSource {
+    /// Direct hits are blocked by a shield, damage over time is not
+    pub fn is_hit(&self) -> bool {
+        matches!(
+            self,
+            DamageSource::Fireball | DamageSource::Poop | DamageSource::Bash
+        )
+    }
+
+    pub fn verb(&self) -> &'static str {
+        match self {
+            DamageSource::Fireball => "roasted",
+            DamageSource::Poop => "pooped on",
+            DamageSource::Disconnect => "disconnected",
+            DamageSource::Poison => "poisoned",
+            DamageSource::Burning => "burned",
+            DamageSource::Bash => "shell-bashed",
+        }
+    }
+}
+
+/// Tracks who landed the most recent hit on a player so eliminations can be credited
+#[derive(Clone, Component, Debug, Default, Hash, Reflect)]
+#[reflect(Component, Hash)]
+pub struct LastHitBy {
+    pub attacker: Option<usize>,
+    pub source: DamageSource,
+    pub frame: i32,
+}
+
+/// A single kill feed message, spawned as a rollback entity so mispredicted kills disappear
+#[derive(Clone, Component, Debug, Default, Hash, Reflect)]
+#[reflect(Component, Hash)]
+pub struct KillFeedEntry {
+    pub attacker: Option<usize>,
+    pub victim: usize,
+    pub source: DamageSource,
+    pub frame: i32,
+}
+
+impl KillFeedEntry {
+    pub fn message(&self, names: &PlayerNames) -> String {
+        match self.attacker {
+            Some(attacker) => format!(
+                "{} {} {}",
+                names.get(attacker),
+                self.source.verb(),
+                names.get(self.victim)
+            ),
+            None if self.source == DamageSource::Disconnect => {
+                format!("{} disconnected", names.get(self.victim))
+            }
+            None => format!("{} was eliminated", names.get(self.victim)),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct KillFeedText;
+
+#[derive(Component)]
+pub struct NetworkNoticeText;
+
+/// One row of the network HUD, indexed by remote peer order
+#[derive(Component)]
+pub struct NetworkHudRow(pub usize);
+
+#[derive(Component)]
+pub struct NetworkHudText(pub usize);
+
+#[derive(Component)]
+pub struct NetworkQualityIcon(pub usize);
+
+#[derive(Component)]
+pub struct NetworkWarningText;
+
 #[derive(Component)]
 pub struct PlayerHealthText;
 
 #[derive(Component)]
 pub struct PlayerFireballText;
 
+#[derive(Component)]
+pub struct PlayerPoopText;
+
 #[derive(Component)]
 pub struct PlayerSpeedBoostText;
 
+#[derive(Component)]
+pub struct PlayerLivesText;
+
+#[derive(Component)]
+pub struct PlayerScoreText;
+
+#[derive(Component)]
+pub struct RoundTimerText;
+
 impl Default for Player {
     fn default() -> Self {
         Player {
@@ -0,0 +1,215 @@
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use bevy::reflect::TypePath;
+use bevy::utils::BoxedFuture;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::status::StatusKind;
+use crate::menu::handshake::stable_hash;
+
+/// Where an edible type appears when its spawn timer fires
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum SpawnZone {
+    /// One at a random encounter spawner
+    Encounter,
+    /// One on every player spawn point not blocked by a player or the same edible
+    PlayerSpawns,
+}
+
+/// What happens to the player who eats an edible
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum EdibleEffect {
+    /// Adds fireballs
+    Ammo(i32),
+    /// Adds poops to drop
+    PoopAmmo(i32),
+    /// Adds speed boost, capped at `PLAYER_SPEED_BOOST_MAX`
+    SpeedBoost(i32),
+    /// Restores health, capped at `PLAYER_HEALTH_MAX`
+    Heal(i32),
+    /// Makes the player invulnerable for this many seconds
+    Invulnerable(f32),
+    /// Applies a status effect for this many seconds
+    Status(StatusKind, f32),
+    /// Shield absorbing this many hits, for at most this many seconds
+    Shield(u32, f32),
+}
+
+/// One kind of edible as described in `assets/edibles/base.edibles.ron`
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EdibleDef {
+    pub name: String,
+    /// Texture path relative to the assets folder
+    pub texture: String,
+    /// Pickup size in pixels
+    pub size: f32,
+    /// Drawn size in pixels, the texture's own size when missing
+    #[serde(default)]
+    pub sprite_size: Option<f32>,
+    /// Seconds between spawns
+    pub spawn_rate: f32,
+    /// No more spawn while this many are on the map
+    pub max_on_map: usize,
+    pub spawn_zones: Vec<SpawnZone>,
+    pub effects: Vec<EdibleEffect>,
+    #[serde(skip)]
+    pub texture_handle: Handle<Image>,
+}
+
+/// Every edible in the game, loaded from `assets/edibles/base.edibles.ron` and inserted as a
+/// resource once loading is done. The `Edible` component stores an index into `edibles`.
+#[derive(Asset, Clone, Debug, Default, Resource, TypePath)]
+pub struct EdibleRegistry {
+    pub edibles: Vec<EdibleDef>,
+    /// Hash of the parsed definitions, peers compare it before playing together
+    pub hash: u64,
+}
+
+impl EdibleRegistry {
+    /// Parses the definitions without loading their textures. The hash covers the definitions
+    /// written back out, so line endings and comments in the file don't change it.
+    pub fn from_ron(bytes: &[u8]) -> Result<EdibleRegistry, EdibleRegistryLoaderError> {
+        let edibles: Vec<EdibleDef> =
+            ron::de::from_bytes(bytes).map_err(EdibleRegistryLoaderError::Ron)?;
+        if edibles.is_empty() {
+            return Err(EdibleRegistryLoaderError::Empty);
+        }
+        let normalized = ron::to_string(&edibles).map_err(EdibleRegistryLoaderError::Hash)?;
+        Ok(EdibleRegistry {
+            hash: stable_hash(normalized.as_bytes()),
+            edibles,
+        })
+    }
+
+    /// The default registry is empty, it only gets edibles once the file loaded
+    pub fn is_loaded(&self) -> bool {
+        !self.edibles.is_empty()
+    }
+
+    pub fn get(&self, kind: usize) -> Option<&EdibleDef> {
+        self.edibles.get(kind)
+    }
+
+    pub fn name(&self, kind: usize) -> &str {
+        match self.edibles.get(kind) {
+            Some(def) => &def.name,
+            None => "Unknown",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum EdibleRegistryLoaderError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    Hash(ron::Error),
+    Empty,
+}
+
+impl fmt::Display for EdibleRegistryLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EdibleRegistryLoaderError::Io(e) => write!(f, "unable to read edibles: {}", e),
+            EdibleRegistryLoaderError::Ron(e) => write!(f, "invalid edibles file: {}", e),
+            EdibleRegistryLoaderError::Hash(e) => write!(f, "unable to hash edibles: {}", e),
+            EdibleRegistryLoaderError::Empty => write!(f, "edibles file defines no edibles"),
+        }
+    }
+}
+
+impl std::error::Error for EdibleRegistryLoaderError {}
+
+#[derive(Default)]
+pub struct EdibleRegistryLoader;
+
+impl AssetLoader for EdibleRegistryLoader {
+    type Asset = EdibleRegistry;
+    type Settings = ();
+    type Error = EdibleRegistryLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<EdibleRegistry, EdibleRegistryLoaderError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader
+                .read_to_end(&mut bytes)
+                .await
+                .map_err(EdibleRegistryLoaderError::Io)?;
+            let mut registry = EdibleRegistry::from_ron(&bytes)?;
+            for def in registry.edibles.iter_mut() {
+                def.texture_handle = load_context.load(def.texture.clone());
+            }
+            Ok(registry)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["edibles.ron"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base_file() -> String {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/assets/edibles/base.edibles.ron"
+        );
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn base_file_parses() {
+        let registry = EdibleRegistry::from_ron(base_file().as_bytes()).unwrap();
+        assert!(!registry.edibles.is_empty());
+        assert_eq!(registry.name(0), "Strawberry");
+    }
+
+    #[test]
+    fn empty_file_is_rejected() {
+        assert!(matches!(
+            EdibleRegistry::from_ron(b"[]"),
+            Err(EdibleRegistryLoaderError::Empty)
+        ));
+        assert!(!EdibleRegistry::default().is_loaded());
+    }
+
+    #[test]
+    fn hash_ignores_line_endings_and_comments() {
+        let file = base_file();
+        let hash = EdibleRegistry::from_ron(file.as_bytes()).unwrap().hash;
+
+        let crlf = file.replace('\n', "\r\n");
+        assert_eq!(
+            EdibleRegistry::from_ron(crlf.as_bytes()).unwrap().hash,
+            hash
+        );
+
+        let commented = format!("// extra notes for designers\n{}", file);
+        assert_eq!(
+            EdibleRegistry::from_ron(commented.as_bytes()).unwrap().hash,
+            hash
+        );
+    }
+
+    #[test]
+    fn hash_changes_with_the_rules() {
+        let file = base_file();
+        let hash = EdibleRegistry::from_ron(file.as_bytes()).unwrap().hash;
+
+        let changed = file.replacen("max_on_map: 16", "max_on_map: 17", 1);
+        assert_ne!(changed, file);
+        assert_ne!(
+            EdibleRegistry::from_ron(changed.as_bytes()).unwrap().hash,
+            hash
+        );
+    }
+}
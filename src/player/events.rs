@@ -0,0 +1,14 @@
+use bevy::prelude::*;
+
+use super::components::DamageSource;
+
+/// Sent by the rollback systems whenever a player takes damage,
+/// consumed by `apply_damage` in the same frame.
+#[derive(Clone, Copy, Debug, Event)]
+pub struct DamageEvent {
+    pub attacker: usize,
+    pub victim: usize,
+    pub source: DamageSource,
+    pub amount: i32,
+    pub frame: i32,
+}
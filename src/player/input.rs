@@ -3,6 +3,9 @@ use bevy_ggrs::*;
 use bevy_matchbox::matchbox_socket::PeerId;
 use bytemuck::{Pod, Zeroable};
 
+use crate::chat::resources::ChatInput;
+use crate::physics::FixedVec2;
+
 #[derive(Debug)]
 pub struct GGRSConfig;
 impl ggrs::Config for GGRSConfig {
@@ -12,34 +15,51 @@ impl ggrs::Config for GGRSConfig {
 }
 
 #[derive(Clone, Default, Reflect, Component)]
-
 pub struct PlayerControls {
-    pub dir: Vec2,
-    pub last_dir: Vec2,
+    pub dir: FixedVec2,
+    pub last_dir: FixedVec2,
     pub exiting: bool,
     pub shooting: bool,
+    pub bashing: bool,
+    pub pooping: bool,
     pub sprinting: bool,
 }
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
 pub struct PlayerInput {
-    pub input: u8,
+    pub input: u16,
 }
 
-pub const INPUT_UP: u8 = 1 << 0;
-pub const INPUT_DOWN: u8 = 1 << 1;
-pub const INPUT_LEFT: u8 = 1 << 2;
-pub const INPUT_RIGHT: u8 = 1 << 3;
-pub const INPUT_FIRE: u8 = 1 << 4;
-pub const INPUT_EXIT: u8 = 1 << 5;
-pub const INPUT_SPRINT: u8 = 1 << 6;
+/// Bump whenever PlayerInput or the meaning of its bits changes, peers compare it in the lobby handshake
+pub const INPUT_FORMAT_VERSION: u32 = 3;
 
-pub fn input(mut commands: Commands, keys: Res<Input<KeyCode>>, local_players: Res<LocalPlayers>) {
+pub const INPUT_UP: u16 = 1 << 0;
+pub const INPUT_DOWN: u16 = 1 << 1;
+pub const INPUT_LEFT: u16 = 1 << 2;
+pub const INPUT_RIGHT: u16 = 1 << 3;
+pub const INPUT_FIRE: u16 = 1 << 4;
+pub const INPUT_BASH: u16 = 1 << 7;
+pub const INPUT_POOP: u16 = 1 << 8;
+pub const INPUT_EXIT: u16 = 1 << 5;
+pub const INPUT_SPRINT: u16 = 1 << 6;
+
+pub fn input(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    local_players: Res<LocalPlayers>,
+    chat: Res<ChatInput>,
+) {
     let mut local_inputs = HashMap::new();
 
     for handle in &local_players.0 {
-        let mut input: u8 = 0;
+        let mut input: u16 = 0;
+
+        // keys typed into the chat prompt should not move the turtle
+        if chat.open {
+            local_inputs.insert(*handle, PlayerInput { input });
+            continue;
+        }
 
         if keys.pressed(KeyCode::W) {
             input |= INPUT_UP;
@@ -56,6 +76,12 @@ pub fn input(mut commands: Commands, keys: Res<Input<KeyCode>>, local_players: R
         if keys.any_pressed([KeyCode::Space, KeyCode::Return]) {
             input |= INPUT_FIRE;
         }
+        if keys.pressed(KeyCode::E) {
+            input |= INPUT_BASH;
+        }
+        if keys.pressed(KeyCode::Q) {
+            input |= INPUT_POOP;
+        }
         if keys.any_pressed([KeyCode::Escape, KeyCode::Delete]) {
             input |= INPUT_EXIT;
         }
@@ -1,7 +1,13 @@
 pub mod checksum;
 pub mod components;
+pub mod edibles;
+pub mod events;
 pub mod input;
+pub mod network;
 pub mod plugin;
+pub mod projectiles;
 pub mod resources;
 mod round;
+pub mod spectate;
+pub mod status;
 pub mod systems;
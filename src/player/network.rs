@@ -0,0 +1,240 @@
+use bevy::prelude::*;
+use bevy_ggrs::{RollbackFrameCount, Session};
+use ggrs::{GgrsEvent, NetworkStats};
+
+use super::components::{
+    NetworkHudRow, NetworkHudText, NetworkNoticeText, NetworkQualityIcon, NetworkWarningText,
+};
+use super::input::GGRSConfig;
+use super::resources::PlayerNames;
+
+/// How long a network notice stays on screen
+pub const NETWORK_NOTICE_SECS: f64 = 5.0;
+/// How often the network HUD polls GGRS for fresh stats
+pub const NETWORK_HUD_INTERVAL: f32 = 0.5;
+/// How long the wait warning stays up after GGRS recommends waiting
+pub const WAIT_WARNING_SECS: f64 = 2.0;
+/// The simulation counts as stalled when no frame advanced for this long
+pub const STALL_WARNING_SECS: f64 = 0.25;
+pub const PING_GOOD_MS: u128 = 80;
+pub const PING_OK_MS: u128 = 150;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConnectionQuality {
+    Good,
+    Ok,
+    Bad,
+    Unknown,
+}
+
+impl ConnectionQuality {
+    pub fn from_stats(stats: &NetworkStats) -> ConnectionQuality {
+        if stats.ping < PING_GOOD_MS {
+            ConnectionQuality::Good
+        } else if stats.ping < PING_OK_MS {
+            ConnectionQuality::Ok
+        } else {
+            ConnectionQuality::Bad
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            ConnectionQuality::Good => Color::LIME_GREEN,
+            ConnectionQuality::Ok => Color::YELLOW,
+            ConnectionQuality::Bad => Color::RED,
+            ConnectionQuality::Unknown => Color::GRAY,
+        }
+    }
+}
+
+/// Local bookkeeping for the network HUD and its warnings
+#[derive(Resource)]
+pub struct NetworkHud {
+    pub timer: Timer,
+    pub wait_frames: u32,
+    pub wait_until: f64,
+    pub last_frame: i32,
+    pub last_frame_at: f64,
+}
+
+impl Default for NetworkHud {
+    fn default() -> Self {
+        NetworkHud {
+            timer: Timer::from_seconds(NETWORK_HUD_INTERVAL, TimerMode::Repeating),
+            wait_frames: 0,
+            wait_until: 0.,
+            last_frame: 0,
+            last_frame_at: 0.,
+        }
+    }
+}
+
+/// On-screen messages about the connection to other peers, these are local only
+/// and never affect the simulation.
+#[derive(Default, Resource)]
+pub struct NetworkNotices(pub Vec<(String, f64)>);
+
+impl NetworkNotices {
+    pub fn push(&mut self, text: String, now: f64) {
+        self.0.push((text, now + NETWORK_NOTICE_SECS));
+    }
+}
+
+pub fn handle_ggrs_events(
+    time: Res<Time<Real>>,
+    names: Res<PlayerNames>,
+    mut notices: ResMut<NetworkNotices>,
+    mut hud: ResMut<NetworkHud>,
+    mut session: ResMut<Session<GGRSConfig>>,
+) {
+    let now = time.elapsed_seconds_f64();
+
+    let s = match session.as_mut() {
+        Session::P2P(s) => s,
+        _ => return,
+    };
+
+    let events = s.events().collect::<Vec<_>>();
+    for event in events {
+        info!("GGRS Event: {:?}", event);
+        let peer_name = |addr| {
+            let handles = s.handles_by_address(addr);
+            match handles.first() {
+                Some(handle) => names.get(*handle),
+                None => format!("{}", addr),
+            }
+        };
+        match event {
+            GgrsEvent::Disconnected { addr } => {
+                notices.push(format!("{} disconnected", peer_name(addr)), now);
+            }
+            GgrsEvent::NetworkInterrupted {
+                addr,
+                disconnect_timeout,
+            } => {
+                notices.push(
+                    format!(
+                        "Connection to {} interrupted, dropping in {:.1}s",
+                        peer_name(addr),
+                        disconnect_timeout as f32 / 1000.
+                    ),
+                    now,
+                );
+            }
+            GgrsEvent::NetworkResumed { addr } => {
+                notices.push(format!("{} reconnected", peer_name(addr)), now);
+            }
+            GgrsEvent::WaitRecommendation { skip_frames } => {
+                hud.wait_frames = skip_frames;
+                hud.wait_until = now + WAIT_WARNING_SECS;
+            }
+            _ => (),
+        }
+    }
+}
+
+pub fn update_network_notice_text(
+    time: Res<Time<Real>>,
+    mut notices: ResMut<NetworkNotices>,
+    mut text_query: Query<&mut Text, With<NetworkNoticeText>>,
+) {
+    let now = time.elapsed_seconds_f64();
+    notices.0.retain(|(_, expires)| *expires > now);
+
+    let lines = notices
+        .0
+        .iter()
+        .map(|(text, _)| text.clone())
+        .collect::<Vec<_>>();
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = lines.join("\n");
+    }
+}
+
+fn frames_label(stats: &NetworkStats) -> String {
+    match stats.local_frames_behind {
+        0 => "in sync".to_owned(),
+        f if f > 0 => format!("{} behind", f),
+        f => format!("{} ahead", -f),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_network_hud(
+    time: Res<Time<Real>>,
+    frame: Res<RollbackFrameCount>,
+    names: Res<PlayerNames>,
+    session: Res<Session<GGRSConfig>>,
+    mut hud: ResMut<NetworkHud>,
+    mut row_query: Query<(&NetworkHudRow, &mut Style)>,
+    mut icon_query: Query<(&NetworkQualityIcon, &mut BackgroundColor)>,
+    mut text_query: Query<(&NetworkHudText, &mut Text), Without<NetworkWarningText>>,
+    mut warning_query: Query<&mut Text, (With<NetworkWarningText>, Without<NetworkHudText>)>,
+) {
+    let now = time.elapsed_seconds_f64();
+
+    // the rollback frame only stands still when GGRS can't advance without remote inputs
+    let frame = i32::from(*frame);
+    if frame != hud.last_frame || hud.last_frame_at == 0. {
+        hud.last_frame = frame;
+        hud.last_frame_at = now;
+    }
+    let warning = if now - hud.last_frame_at > STALL_WARNING_SECS {
+        "Waiting for inputs from a slow peer...".to_owned()
+    } else if now < hud.wait_until {
+        format!(
+            "Running ahead, waiting {} frame(s) for peers",
+            hud.wait_frames
+        )
+    } else {
+        "".to_owned()
+    };
+    for mut text in warning_query.iter_mut() {
+        text.sections[0].value = warning.clone();
+    }
+
+    if !hud.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let s = match session.as_ref() {
+        Session::P2P(s) => s,
+        _ => return,
+    };
+    let mut handles = s.remote_player_handles();
+    handles.sort();
+
+    for (row, mut style) in row_query.iter_mut() {
+        style.display = if row.0 < handles.len() {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+
+    for (icon, mut color) in icon_query.iter_mut() {
+        let quality = match handles.get(icon.0).map(|h| s.network_stats(*h)) {
+            Some(Ok(stats)) => ConnectionQuality::from_stats(&stats),
+            _ => ConnectionQuality::Unknown,
+        };
+        *color = BackgroundColor(quality.color());
+    }
+
+    for (row, mut text) in text_query.iter_mut() {
+        let handle = match handles.get(row.0) {
+            Some(handle) => *handle,
+            None => continue,
+        };
+        text.sections[0].value = match s.network_stats(handle) {
+            Ok(stats) => format!(
+                "{}: {}ms, {}, {}kbps",
+                names.get(handle),
+                stats.ping,
+                frames_label(&stats),
+                stats.kbps_sent
+            ),
+            Err(_) => format!("{}: no connection", names.get(handle)),
+        };
+    }
+}
@@ -1,13 +1,30 @@
 use super::checksum::checksum_players;
 use super::components::EdibleSpawnTimer;
-use super::resources::{HealthBarsAdded, PlayersReady};
+use super::edibles::EdibleRegistry;
+use super::events::DamageEvent;
+use super::input::GGRSConfig;
+use super::network::{
+    handle_ggrs_events, update_network_hud, update_network_notice_text, NetworkHud, NetworkNotices,
+};
+use super::projectiles::{
+    despawn_old_projectiles, face_projectiles_forward, move_projectiles, projectile_damage_geese,
+    projectile_damage_players, tick_projectile_timers,
+};
+use super::resources::{
+    HealthBarsAdded, InputHistory, MatchSettings, PlayerNames, PlayersReady, Spectator,
+};
 use super::round::{
     cleanup_round, cleanup_session, disconnect_remote_players, remove_expired, setup_round,
 };
+use super::spectate::{
+    announce_spectating, pace_spectator_clock, reset_spectator_clock, sync_input_history,
+};
+use super::status::{hide_invisible_players, tick_status_effects, update_status_effect_hud};
+use crate::physics::SyncTransformsSet;
 use crate::player::systems::*;
 use crate::{AppState, GameState};
 use bevy::prelude::*;
-use bevy_ggrs::GgrsSchedule;
+use bevy_ggrs::{GgrsSchedule, Session};
 
 pub struct PlayerPlugin;
 
@@ -16,6 +33,13 @@ pub struct PlayerPlugin;
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<EdibleSpawnTimer>()
+            .init_resource::<EdibleRegistry>()
+            .init_resource::<PlayerNames>()
+            .init_resource::<MatchSettings>()
+            .init_resource::<NetworkNotices>()
+            .init_resource::<NetworkHud>()
+            .init_resource::<InputHistory>()
+            .add_event::<DamageEvent>()
             // round setup
             .add_systems(
                 OnEnter(GameState::Playing),
@@ -29,9 +53,35 @@ impl Plugin for PlayerPlugin {
                 Update,
                 update_health_bars.run_if(resource_exists::<HealthBarsAdded>()),
             )
-            .add_systems(Update, camera_follow.run_if(in_state(GameState::Playing)))
+            .add_systems(
+                Update,
+                camera_follow
+                    .after(SyncTransformsSet)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            // connection notices, local only and outside of rollback
+            .add_systems(
+                Update,
+                (
+                    handle_ggrs_events.run_if(resource_exists::<Session<GGRSConfig>>()),
+                    update_network_notice_text,
+                    update_network_hud.run_if(resource_exists::<Session<GGRSConfig>>()),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            // spectators replay the round from inputs streamed by the players
+            .add_systems(
+                Update,
+                (announce_spectating, pace_spectator_clock)
+                    .run_if(resource_exists::<Spectator>())
+                    .run_if(in_state(GameState::Playing)),
+            )
             // round cleanup
-            .add_systems(OnExit(AppState::RoundOnline), disconnect_remote_players)
+            .add_systems(
+                OnExit(AppState::RoundOnline),
+                (disconnect_remote_players, reset_spectator_clock),
+            )
             .add_systems(
                 OnEnter(AppState::Win),
                 (cleanup_session, cleanup_round).chain(),
@@ -43,7 +93,16 @@ impl Plugin for PlayerPlugin {
                     check_win_state,
                     update_player_health_text,
                     update_player_fireball_text,
+                    update_player_poop_text,
                     update_player_speed_boost_text,
+                    update_player_lives_text,
+                    update_player_score_text,
+                    update_round_timer_text,
+                    update_kill_feed_text,
+                    tint_player_sprites,
+                    face_projectiles_forward,
+                    hide_invisible_players,
+                    update_status_effect_hud,
                 )
                     .distributive_run_if(in_state(GameState::Playing)),
             )
@@ -52,18 +111,31 @@ impl Plugin for PlayerPlugin {
             .add_systems(
                 GgrsSchedule,
                 (
+                    sync_input_history,
                     apply_inputs,
+                    eliminate_disconnected_players,
                     set_walking_sound,
                     apply_player_sprint,
                     move_players,
                     checksum_players,
                     reload_fireballs,
                     shoot_fireballs,
-                    move_fireballs,
-                    fireball_damage_players,
-                    kill_players,
+                    // bevy system tuples hold at most 20 items
+                    (
+                        move_projectiles,
+                        projectile_damage_players,
+                        projectile_damage_geese,
+                    )
+                        .chain(),
+                    shell_bash,
                     player_poops,
                     player_stepped_in_poop,
+                    tick_status_effects,
+                    apply_damage,
+                    kill_players,
+                    respawn_players,
+                    tick_invulnerability,
+                    tick_round_timer,
                 )
                     .chain()
                     .in_set(PlayerSystemSet)
@@ -73,18 +145,15 @@ impl Plugin for PlayerPlugin {
             .add_systems(
                 GgrsSchedule,
                 (
-                    spawn_strawberry_on_player_spawn_points,
-                    spawn_strawberry_over_time,
-                    spawn_chili_pepper_over_time,
-                    spawn_lettuce_over_time,
+                    spawn_edibles_over_time,
                     tick_edible_timer,
-                    player_ate_chili_pepper_system,
-                    player_ate_strawberry_system,
-                    player_ate_lettuce_system,
-                    despawn_old_fireballs,
+                    player_ate_edible_system,
+                    despawn_old_projectiles,
                     despawn_old_poops,
-                    tick_fireball_timers,
+                    despawn_old_kill_feed_entries,
+                    tick_projectile_timers,
                     tick_poop_timers,
+                    tick_bash_cooldowns,
                     remove_expired,
                 )
                     .chain()
@@ -0,0 +1,394 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_ggrs::{AddRollbackCommandExtension, Rollback, RollbackFrameCount};
+
+use super::components::{
+    DamageSource, Expired, Player, PlayerInvulnerable, RoundComponent, FIREBALL_HOMING_RANGE,
+    FIREBALL_HOMING_TURN_COS, FIREBALL_HOMING_TURN_SIN,
+};
+use super::events::DamageEvent;
+use super::status::{StatusEffects, StatusKind};
+use crate::loading::TextureAssets;
+use crate::map::tilemap::TileCollider;
+use crate::npc::components::Goose;
+use crate::npc::events::GooseHitEvent;
+use crate::physics::{Fixed, FixedVec2, Position};
+use crate::{FIXED_TICK_MS, FPS, TILE_SIZE};
+
+/// Stats shared by every projectile of one kind
+#[derive(Clone, Copy, Debug)]
+pub struct ProjectileDef {
+    pub name: &'static str,
+    /// Units per second
+    pub speed: Fixed,
+    pub radius: Fixed,
+    pub damage: i32,
+    /// Seconds before it disappears on its own
+    pub lifetime: f32,
+    /// Players it passes through before the next hit uses it up
+    pub pierce: u32,
+    /// Whether walls stop it, fireballs fly over them
+    pub hits_walls: bool,
+    /// Walls it bounces off before the next wall uses it up
+    pub bounce: u32,
+    pub source: DamageSource,
+}
+
+pub const FIREBALL: ProjectileDef = ProjectileDef {
+    name: "Fireball",
+    speed: Fixed::from_int(300),
+    radius: Fixed::from_int(12),
+    damage: 5,
+    lifetime: 10.0,
+    pierce: 0,
+    hits_walls: false,
+    bounce: 0,
+    source: DamageSource::Fireball,
+};
+
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, Reflect)]
+pub enum ProjectileKind {
+    #[default]
+    Fireball,
+}
+
+impl ProjectileKind {
+    pub fn def(&self) -> &'static ProjectileDef {
+        match self {
+            ProjectileKind::Fireball => &FIREBALL,
+        }
+    }
+
+    pub fn texture(&self, textures: &TextureAssets) -> Handle<Image> {
+        match self {
+            ProjectileKind::Fireball => textures.texture_fireball.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Component, Debug, Default, Hash, Reflect)]
+#[reflect(Component, Hash)]
+pub struct Projectile {
+    pub kind: ProjectileKind,
+    pub shot_by: usize,
+    pub pierces_left: u32,
+    pub bounces_left: u32,
+    /// Handles of the players already hit, a piercing projectile damages each of them once
+    pub hit: Vec<usize>,
+    /// Ids of the geese already hit
+    pub hit_geese: Vec<u32>,
+}
+
+#[derive(Clone, Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct ProjectileMovement {
+    pub dir: FixedVec2,
+    /// Units per second
+    pub speed: Fixed,
+}
+
+/// Projectile turning towards the nearest enemy player
+#[derive(Clone, Component, Default, Hash, Reflect)]
+#[reflect(Component, Hash)]
+pub struct ProjectileHoming;
+
+#[derive(Clone, Component, Reflect)]
+#[reflect(Component)]
+pub struct ProjectileTimer {
+    pub lifetime: Timer,
+}
+
+impl ProjectileTimer {
+    pub fn new(kind: ProjectileKind) -> Self {
+        ProjectileTimer {
+            lifetime: Timer::from_seconds(kind.def().lifetime, TimerMode::Once),
+        }
+    }
+}
+
+impl Default for ProjectileTimer {
+    fn default() -> Self {
+        ProjectileTimer::new(ProjectileKind::default())
+    }
+}
+
+/// Spawns a rollback projectile flying in `dir` from `pos`
+pub fn spawn_projectile<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    textures: &TextureAssets,
+    kind: ProjectileKind,
+    shot_by: usize,
+    pos: FixedVec2,
+    dir: FixedVec2,
+) -> bevy::ecs::system::EntityCommands<'w, 's, 'a> {
+    let def = kind.def();
+    let mut projectile = commands.spawn((
+        Name::new(def.name),
+        Projectile {
+            kind,
+            shot_by,
+            pierces_left: def.pierce,
+            bounces_left: def.bounce,
+            hit: Vec::new(),
+            hit_geese: Vec::new(),
+        },
+        ProjectileMovement {
+            dir,
+            speed: def.speed,
+        },
+        ProjectileTimer::new(kind),
+        RoundComponent,
+        Position(pos),
+        SpriteBundle {
+            transform: Transform::from_translation(pos.extend(1.))
+                .with_rotation(Quat::from_rotation_arc_2d(Vec2::X, dir.to_vec2())),
+            texture: kind.texture(textures),
+            ..default()
+        },
+    ));
+    projectile.add_rollback();
+    projectile
+}
+
+/// Projectiles still in play
+type LiveProjectile = (With<Rollback>, Without<Expired>);
+type MovingProjectile = (
+    Entity,
+    &'static mut Position,
+    &'static mut ProjectileMovement,
+    &'static mut Projectile,
+    Option<&'static ProjectileHoming>,
+);
+type HittingProjectile = (Entity, &'static Position, &'static mut Projectile);
+type HittablePlayer = (
+    With<Rollback>,
+    Without<Projectile>,
+    Without<PlayerInvulnerable>,
+);
+type HittableGoose = (Without<Projectile>, Without<Expired>);
+
+fn hits_wall(pos: FixedVec2, radius: Fixed, walls: &[FixedVec2]) -> bool {
+    let reach = Fixed::from_f32(TILE_SIZE / 2.) + radius;
+    walls.iter().any(|wall| {
+        let offset = pos - *wall;
+        offset.x.abs() < reach && offset.y.abs() < reach
+    })
+}
+
+/// Moves every projectile along its direction, homing projectiles first turn a little towards
+/// the nearest visible enemy in range. For projectiles stopped by walls, a wall flips the
+/// direction on the axis it was hit while bounces are left and uses the projectile up otherwise.
+pub fn move_projectiles(
+    mut commands: Commands,
+    mut query: Query<MovingProjectile, LiveProjectile>,
+    player_query: Query<(Entity, &Position, &Player, &StatusEffects), Without<Projectile>>,
+    wall_query: Query<&Position, (With<TileCollider>, Without<Projectile>)>,
+) {
+    let mut players = player_query.iter().collect::<Vec<_>>();
+    players.sort_by_key(|p| p.0);
+
+    let walls = wall_query.iter().map(|w| w.0).collect::<Vec<_>>();
+
+    // collect and sort all projectiles in play so we move them in a deterministic order
+    let mut projectiles = query.iter_mut().collect::<Vec<_>>();
+    projectiles.sort_by_key(|t| t.0);
+
+    for (entity, mut position, mut movement, mut projectile, homing) in projectiles {
+        if homing.is_some() {
+            let pos = position.0;
+            // ties go to the first player in entity order
+            let target = players
+                .iter()
+                .filter(|(_, _, p, effects)| {
+                    p.active
+                        && p.handle != projectile.shot_by
+                        && !effects.has(StatusKind::Invisibility)
+                })
+                .map(|(_, p, _, _)| p.0)
+                .filter(|p| p.distance(pos) < FIREBALL_HOMING_RANGE)
+                .min_by_key(|p| p.distance(pos));
+
+            let wanted = target.map(|t| (t - pos).normalize_or_zero());
+            if let Some(wanted) = wanted.filter(|w| *w != FixedVec2::ZERO) {
+                if movement.dir.dot(wanted) >= FIREBALL_HOMING_TURN_COS {
+                    movement.dir = wanted; // close enough to turn all the way this frame
+                } else if movement.dir.perp_dot(wanted) > Fixed::ZERO {
+                    movement.dir = movement
+                        .dir
+                        .rotate(FIREBALL_HOMING_TURN_COS, FIREBALL_HOMING_TURN_SIN);
+                } else {
+                    movement.dir = movement
+                        .dir
+                        .rotate(FIREBALL_HOMING_TURN_COS, -FIREBALL_HOMING_TURN_SIN);
+                }
+            }
+        }
+
+        let def = projectile.kind.def();
+        let step = movement.dir * movement.speed / FPS as i32;
+        if !def.hits_walls {
+            position.0 += step;
+            continue;
+        }
+
+        // one axis at a time so a bounce only flips the axis that hit the wall
+        for axis in [
+            FixedVec2::new(step.x, Fixed::ZERO),
+            FixedVec2::new(Fixed::ZERO, step.y),
+        ] {
+            if axis == FixedVec2::ZERO {
+                continue;
+            }
+            let target = position.0 + axis;
+            if !hits_wall(target, def.radius, &walls) {
+                position.0 = target;
+                continue;
+            }
+            if projectile.bounces_left == 0 {
+                commands.entity(entity).insert(Expired);
+                break;
+            }
+            projectile.bounces_left -= 1;
+            if axis.x != Fixed::ZERO {
+                movement.dir.x = -movement.dir.x;
+            } else {
+                movement.dir.y = -movement.dir.y;
+            }
+        }
+    }
+}
+
+/// Points projectile sprites the way they fly, this is visual only
+pub fn face_projectiles_forward(
+    mut query: Query<(&ProjectileMovement, &mut Transform), Changed<ProjectileMovement>>,
+) {
+    for (movement, mut transform) in query.iter_mut() {
+        if movement.dir != FixedVec2::ZERO {
+            transform.rotation = Quat::from_rotation_arc_2d(Vec2::X, movement.dir.to_vec2());
+        }
+    }
+}
+
+pub fn tick_projectile_timers(mut query: Query<(Entity, &mut ProjectileTimer), Without<Expired>>) {
+    // collect and sort all timers in play so we tick them in a deterministic order
+    let mut timers = query.iter_mut().collect::<Vec<_>>();
+    timers.sort_by_key(|t| t.0);
+
+    for (_, mut timer) in timers {
+        timer.lifetime.tick(Duration::from_millis(FIXED_TICK_MS));
+    }
+}
+
+pub fn despawn_old_projectiles(
+    mut commands: Commands,
+    mut query: Query<(Entity, &ProjectileTimer), Without<Expired>>,
+) {
+    trace!("despawn_old_projectiles");
+
+    // collect and sort all projectiles in play so we despawn them in a deterministic order
+    let mut projectiles = query.iter_mut().collect::<Vec<_>>();
+    projectiles.sort_by_key(|e| e.0);
+
+    for (projectile, timer) in projectiles {
+        if timer.lifetime.finished() {
+            debug!("Despawning old projectile {:?}", projectile);
+            commands.entity(projectile).insert(Expired);
+        }
+    }
+}
+
+/// Damages every player a projectile touches, a projectile is used up by the hit after its
+/// last pierce
+pub fn projectile_damage_players(
+    mut commands: Commands,
+    frame: Res<RollbackFrameCount>,
+    mut damage_events: EventWriter<DamageEvent>,
+    player_query: Query<(Entity, &Position, &Player), HittablePlayer>,
+    mut projectile_query: Query<HittingProjectile, LiveProjectile>,
+) {
+    // collect and sort all players and projectiles in play so we damage players in a deterministic order
+    let mut players = player_query.iter().collect::<Vec<_>>();
+    players.sort_by_key(|e| e.0);
+
+    let mut projectiles = projectile_query.iter_mut().collect::<Vec<_>>();
+    projectiles.sort_by_key(|e| e.0);
+
+    for (entity, projectile_pos, mut projectile) in projectiles {
+        let def = projectile.kind.def();
+        for (_, position, player) in players.iter() {
+            if !player.active {
+                continue; // don't continue to damage dead players
+            }
+            if projectile.shot_by == player.handle {
+                continue; // don't allow player to suicide
+            }
+            if projectile.hit.contains(&player.handle) {
+                continue; // pierced through this player already
+            }
+
+            let distance = position.0.distance(projectile_pos.0);
+            if distance >= Fixed::from_f32(TILE_SIZE) + def.radius {
+                continue;
+            }
+
+            damage_events.send(DamageEvent {
+                attacker: projectile.shot_by,
+                victim: player.handle,
+                source: def.source,
+                amount: def.damage,
+                frame: (*frame).into(),
+            });
+            debug!("{} {:?} hit player {:?}", def.name, entity, player.handle);
+
+            if projectile.pierces_left == 0 {
+                commands.entity(entity).insert(Expired); // despawn projectile
+                break;
+            }
+            projectile.pierces_left -= 1;
+            projectile.hit.push(player.handle);
+        }
+    }
+}
+
+/// Same as `projectile_damage_players` for geese, the shooter is credited for every hit
+pub fn projectile_damage_geese(
+    mut commands: Commands,
+    mut goose_hits: EventWriter<GooseHitEvent>,
+    goose_query: Query<(Entity, &Goose, &Position), HittableGoose>,
+    mut projectile_query: Query<HittingProjectile, LiveProjectile>,
+) {
+    let mut geese = goose_query.iter().collect::<Vec<_>>();
+    geese.sort_by_key(|e| e.0);
+
+    let mut projectiles = projectile_query.iter_mut().collect::<Vec<_>>();
+    projectiles.sort_by_key(|e| e.0);
+
+    for (entity, projectile_pos, mut projectile) in projectiles {
+        let def = projectile.kind.def();
+        for (goose_entity, goose, position) in geese.iter() {
+            if projectile.hit_geese.contains(&goose.id) {
+                continue; // pierced through this goose already
+            }
+
+            let distance = position.0.distance(projectile_pos.0);
+            if distance >= Fixed::from_f32(TILE_SIZE) + def.radius {
+                continue;
+            }
+
+            goose_hits.send(GooseHitEvent {
+                goose: *goose_entity,
+                attacker: projectile.shot_by,
+                amount: def.damage,
+            });
+            debug!("{} {:?} hit goose {}", def.name, entity, goose.id);
+
+            if projectile.pierces_left == 0 {
+                commands.entity(entity).insert(Expired); // despawn projectile
+                break;
+            }
+            projectile.pierces_left -= 1;
+            projectile.hit_geese.push(goose.id);
+        }
+    }
+}
@@ -1,23 +1,38 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
 use bevy_matchbox::prelude::PeerId;
 use rand::rngs::StdRng;
 use rand_seeder::Seeder;
+use serde::{Deserialize, Serialize};
 
-#[derive(Resource)]
+use super::components::PLAYER_LIVES_START;
+use crate::map::map::MAPS;
+use crate::npc::components::{GOOSE_MAX, GOOSE_MAX_MAX, GOOSE_MAX_MIN};
+use crate::{CHECK_DISTANCE, FPS, INPUT_DELAY, MAX_PREDICTION};
+
+/// Registered for rollback, edible spawns and goose loot draw from it in the rollback schedule.
+/// Without that a resimulated frame rolls different numbers than the first run and peers desync.
+#[derive(Clone, Resource)]
 pub struct AgreedRandom {
     pub rng: StdRng,
 }
 
 impl AgreedRandom {
-    pub fn new(peers: Vec<PeerId>) -> AgreedRandom {
+    /// Seeds from the sorted peer ids and the round number, so a rematch with
+    /// the same group doesn't replay the previous round
+    pub fn new(peers: Vec<PeerId>, round: u32) -> AgreedRandom {
         let mut tmp = peers.clone();
         tmp.sort();
-        let seed = tmp.iter().fold(String::new(), |mut a, b| {
+        let mut seed = tmp.iter().fold(String::new(), |mut a, b| {
             a.reserve(b.0.to_string().len() + 1);
             a.push_str(b.0.to_string().as_str());
             a.push_str(" ");
             a.trim_end().to_string()
         });
+        if round > 0 {
+            seed.push_str(&format!(" {round}"));
+        }
         let rng: StdRng = Seeder::from(seed).make_rng();
 
         AgreedRandom { rng }
@@ -27,6 +42,420 @@ impl AgreedRandom {
 #[derive(Resource)]
 pub struct PlayersReady;
 
+/// Display names for every player in the session, indexed by handle.
+/// Names are exchanged over the lobby channel before the session starts.
+#[derive(Clone, Debug, Default, Resource)]
+pub struct PlayerNames(pub Vec<String>);
+
+impl PlayerNames {
+    pub fn get(&self, handle: usize) -> String {
+        match self.0.get(handle) {
+            Some(name) if !name.is_empty() => name.clone(),
+            _ => format!("Player {}", handle),
+        }
+    }
+}
+
+/// Confirmed input and disconnect state of every player, one entry per frame of the current
+/// online round. Players stream it to peers joining mid-round, spectators replay the round from it.
+#[derive(Debug, Default, Resource)]
+pub struct InputHistory {
+    pub frames: Vec<Vec<(u16, bool)>>,
+    /// The players left the round, no more frames will arrive
+    pub complete: bool,
+    /// Inputs the frames after `frames` were last simulated with, not all confirmed yet
+    pub pending: Vec<Vec<(u16, bool)>>,
+}
+
+impl InputHistory {
+    /// Remembers the inputs frame `index` was just simulated with and commits every frame
+    /// up to it whose inputs are confirmed. GGRS only resimulates mispredicted frames, so a
+    /// frame that was predicted right is committed from the inputs it was first run with.
+    pub fn record(&mut self, index: usize, inputs: Vec<(u16, bool)>, confirmed_frame: i32) {
+        if index < self.frames.len() {
+            return; // committed already, a rollback never goes back past a confirmed frame
+        }
+        let offset = index - self.frames.len();
+        if offset > self.pending.len() {
+            warn!(
+                "dropping inputs of frame {}, frame {} was never simulated",
+                index,
+                self.frames.len() + self.pending.len()
+            );
+            return;
+        }
+        if offset == self.pending.len() {
+            self.pending.push(inputs);
+        } else {
+            self.pending[offset] = inputs;
+        }
+
+        // later pending frames may still be stale until the rollback reaches them again
+        if confirmed_frame < 0 {
+            return;
+        }
+        let last = index.min(confirmed_frame as usize);
+        if last >= self.frames.len() {
+            let count = last + 1 - self.frames.len();
+            self.frames.extend(self.pending.drain(..count));
+        }
+    }
+
+    /// Appends frames received from a player, frames we already have are skipped
+    pub fn extend_from(&mut self, start: usize, frames: Vec<Vec<(u16, bool)>>) {
+        if start > self.frames.len() {
+            warn!(
+                "dropping inputs from frame {}, still missing frames from {}",
+                start,
+                self.frames.len()
+            );
+            return;
+        }
+        let skip = self.frames.len() - start;
+        self.frames.extend(frames.into_iter().skip(skip));
+    }
+}
+
+/// We joined while a round was running and watch it until the next one starts
+#[derive(Debug, Default, Resource)]
+pub struct Spectator {
+    pub announced: bool,
+}
+
 #[derive(Debug, Default, Reflect, Resource)]
 #[reflect(Resource)]
 pub struct HealthBarsAdded;
+
+pub const ROUND_TIME_LIMIT: f32 = 180.0;
+pub const ROUND_TIME_LIMIT_MIN: u32 = 60;
+pub const ROUND_TIME_LIMIT_MAX: u32 = 600;
+pub const PLAYER_LIVES_MIN: i32 = 1;
+pub const PLAYER_LIVES_MAX: i32 = 9;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Reflect, Serialize)]
+pub enum GameMode {
+    /// Last turtle with lives remaining wins
+    #[default]
+    Elimination,
+    /// Unlimited respawns, most kills when the timer runs out wins
+    Deathmatch,
+}
+
+impl GameMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Elimination => "Elimination",
+            GameMode::Deathmatch => "Deathmatch",
+        }
+    }
+
+    pub fn next(&self) -> GameMode {
+        match self {
+            GameMode::Elimination => GameMode::Deathmatch,
+            GameMode::Deathmatch => GameMode::Elimination,
+        }
+    }
+}
+
+pub const INPUT_DELAY_MAX: usize = 8;
+pub const MAX_PREDICTION_MIN: usize = 1;
+pub const MAX_PREDICTION_MAX: usize = 16;
+pub const CHECK_DISTANCE_MIN: usize = 1;
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Reflect, Serialize)]
+pub enum InputDelay {
+    /// Picked by the host from the ping measured while in the lobby
+    Auto,
+    Fixed(usize),
+}
+
+impl InputDelay {
+    /// Cycles through auto and every fixed delay
+    pub fn next(&self) -> InputDelay {
+        match self {
+            InputDelay::Auto => InputDelay::Fixed(0),
+            InputDelay::Fixed(d) if *d >= INPUT_DELAY_MAX => InputDelay::Auto,
+            InputDelay::Fixed(d) => InputDelay::Fixed(d + 1),
+        }
+    }
+
+    pub fn prev(&self) -> InputDelay {
+        match self {
+            InputDelay::Auto => InputDelay::Fixed(INPUT_DELAY_MAX),
+            InputDelay::Fixed(0) => InputDelay::Auto,
+            InputDelay::Fixed(d) => InputDelay::Fixed(d - 1),
+        }
+    }
+}
+
+/// GGRS tuning, every peer has to build its session from the same values
+#[derive(Clone, Debug, Deserialize, PartialEq, Reflect, Serialize)]
+#[serde(default)]
+pub struct NetcodeSettings {
+    pub input_delay: InputDelay,
+    /// Input delay measured by the host, used when input_delay is Auto
+    pub auto_input_delay: usize,
+    pub max_prediction: usize,
+    /// Only used by the local synctest session
+    pub check_distance: usize,
+}
+
+impl Default for NetcodeSettings {
+    fn default() -> Self {
+        NetcodeSettings {
+            input_delay: InputDelay::Auto,
+            auto_input_delay: INPUT_DELAY,
+            max_prediction: MAX_PREDICTION,
+            check_distance: CHECK_DISTANCE,
+        }
+    }
+}
+
+impl NetcodeSettings {
+    /// Input delay in frames covering half the round trip time, so inputs usually
+    /// arrive before they are needed and rollbacks stay short
+    pub fn input_delay_for_ping(rtt_ms: f64) -> usize {
+        let frame_ms = 1000. / FPS as f64;
+        ((rtt_ms / 2. / frame_ms).ceil() as usize).clamp(1, INPUT_DELAY_MAX)
+    }
+
+    pub fn input_delay(&self) -> usize {
+        match self.input_delay {
+            InputDelay::Auto => self.auto_input_delay,
+            InputDelay::Fixed(d) => d,
+        }
+    }
+
+    /// Synctest sessions refuse a check distance that isn't below the prediction window
+    pub fn check_distance(&self) -> usize {
+        self.check_distance
+            .max(CHECK_DISTANCE_MIN)
+            .min(self.max_prediction.saturating_sub(1))
+    }
+
+    /// Keeps settings loaded from disk or sent by a peer inside the ranges the options menu
+    /// allows, GGRS refuses to start a session outside of them
+    pub fn clamped(mut self) -> NetcodeSettings {
+        if let InputDelay::Fixed(d) = self.input_delay {
+            self.input_delay = InputDelay::Fixed(d.min(INPUT_DELAY_MAX));
+        }
+        self.auto_input_delay = self.auto_input_delay.min(INPUT_DELAY_MAX);
+        self.max_prediction = self
+            .max_prediction
+            .clamp(MAX_PREDICTION_MIN, MAX_PREDICTION_MAX);
+        self.check_distance = self.check_distance();
+        self
+    }
+
+    pub fn describe_input_delay(&self) -> String {
+        match self.input_delay {
+            InputDelay::Auto => format!("auto ({})", self.auto_input_delay),
+            InputDelay::Fixed(d) => d.to_string(),
+        }
+    }
+}
+
+/// Rules for the next round, picked by the lobby host and shared with every peer
+/// before the session starts so all peers simulate the same round.
+#[derive(Clone, Debug, Deserialize, PartialEq, Reflect, Resource, Serialize)]
+#[reflect(Resource)]
+pub struct MatchSettings {
+    pub mode: GameMode,
+    /// Index into `MAPS`
+    pub map: usize,
+    pub lives: i32,
+    pub time_limit: u32,
+    /// Most geese on the map at once
+    pub geese_max: usize,
+    pub netcode: NetcodeSettings,
+}
+
+impl Default for MatchSettings {
+    fn default() -> Self {
+        MatchSettings {
+            mode: GameMode::default(),
+            map: 0,
+            lives: PLAYER_LIVES_START,
+            time_limit: ROUND_TIME_LIMIT as u32,
+            geese_max: GOOSE_MAX,
+            netcode: NetcodeSettings::default(),
+        }
+    }
+}
+
+impl MatchSettings {
+    /// Keeps settings from a peer inside the ranges the lobby menu allows
+    pub fn clamped(mut self) -> MatchSettings {
+        self.map = self.map.min(MAPS.len() - 1);
+        self.lives = self.lives.clamp(PLAYER_LIVES_MIN, PLAYER_LIVES_MAX);
+        self.time_limit = self
+            .time_limit
+            .clamp(ROUND_TIME_LIMIT_MIN, ROUND_TIME_LIMIT_MAX);
+        self.geese_max = self.geese_max.clamp(GOOSE_MAX_MIN, GOOSE_MAX_MAX);
+        self.netcode = self.netcode.clamped();
+        self
+    }
+
+    pub fn describe(&self) -> String {
+        let lives = match self.mode {
+            GameMode::Elimination => self.lives.to_string(),
+            GameMode::Deathmatch => "unlimited".to_owned(),
+        };
+        format!(
+            "Mode: {}\nMap: {}\nLives: {}\nTime limit: {}:{:02}\nGeese: {}\nInput delay: {}\nMax prediction: {}",
+            self.mode.name(),
+            MAPS.get(self.map).map_or("unknown", |map| map.name),
+            lives,
+            self.time_limit / 60,
+            self.time_limit % 60,
+            self.geese_max,
+            self.netcode.describe_input_delay(),
+            self.netcode.max_prediction
+        )
+    }
+}
+
+/// Kills and deaths for each player handle in the current round.
+/// Registered for rollback so kills credited on mispredicted frames are undone.
+#[derive(Clone, Debug, Default, Reflect, Resource)]
+#[reflect(Resource)]
+pub struct RoundScore {
+    pub kills: Vec<i32>,
+    pub deaths: Vec<i32>,
+}
+
+impl RoundScore {
+    pub fn new(num_players: usize) -> RoundScore {
+        RoundScore {
+            kills: vec![0; num_players],
+            deaths: vec![0; num_players],
+        }
+    }
+
+    pub fn credit_kill(&mut self, killer: usize) {
+        if let Some(kills) = self.kills.get_mut(killer) {
+            *kills += 1;
+        }
+    }
+
+    pub fn record_death(&mut self, victim: usize) {
+        if let Some(deaths) = self.deaths.get_mut(victim) {
+            *deaths += 1;
+        }
+    }
+
+    /// Returns the handle with the most kills, ties go to the player with fewer deaths
+    /// and then to the lowest handle so every peer agrees on the winner.
+    pub fn leader(&self) -> Option<usize> {
+        (0..self.kills.len()).max_by(|a, b| {
+            self.kills[*a]
+                .cmp(&self.kills[*b])
+                .then(self.deaths[*b].cmp(&self.deaths[*a]))
+                .then(b.cmp(a))
+        })
+    }
+}
+
+/// Time remaining in a timed deathmatch round, ticked inside the rollback schedule.
+#[derive(Clone, Resource, Reflect)]
+#[reflect(Resource)]
+pub struct RoundTimer(pub Timer);
+
+impl Default for RoundTimer {
+    fn default() -> Self {
+        RoundTimer(Timer::from_seconds(ROUND_TIME_LIMIT, TimerMode::Once))
+    }
+}
+
+impl RoundTimer {
+    pub fn new(time_limit: u32) -> RoundTimer {
+        RoundTimer(Timer::from_seconds(time_limit as f32, TimerMode::Once))
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.0.duration().saturating_sub(self.0.elapsed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inputs(input: u16) -> Vec<(u16, bool)> {
+        vec![(input, false), (input, false)]
+    }
+
+    #[test]
+    fn input_history_commits_frames_predicted_right() {
+        let mut history = InputHistory::default();
+        // nothing confirmed yet, GGRS predicts ahead
+        for frame in 0..3 {
+            history.record(frame, inputs(1), -1);
+        }
+        assert!(history.frames.is_empty());
+
+        // the predictions were right, no rollback happens and frame 3 confirms them
+        history.record(3, inputs(1), 2);
+        assert_eq!(history.frames, vec![inputs(1); 3]);
+        assert_eq!(history.pending, vec![inputs(1)]);
+    }
+
+    #[test]
+    fn input_history_commits_resimulated_frames() {
+        let mut history = InputHistory::default();
+        for frame in 0..3 {
+            history.record(frame, inputs(1), -1);
+        }
+        // frame 1 was mispredicted, the rollback replays it with the real inputs. Frame 2
+        // is confirmed by then but must wait until the rollback resimulated it too.
+        history.record(1, inputs(2), 2);
+        assert_eq!(history.frames, vec![inputs(1), inputs(2)]);
+        history.record(2, inputs(2), 2);
+        assert_eq!(history.frames, vec![inputs(1), inputs(2), inputs(2)]);
+        assert!(history.pending.is_empty());
+
+        // committed frames stay as they are
+        history.record(1, inputs(3), 2);
+        assert_eq!(history.frames[1], inputs(2));
+    }
+
+    #[test]
+    fn match_settings_from_a_peer_are_clamped() {
+        let settings = MatchSettings {
+            map: usize::MAX,
+            lives: 0,
+            time_limit: u32::MAX,
+            geese_max: usize::MAX,
+            ..default()
+        }
+        .clamped();
+        assert_eq!(settings.map, MAPS.len() - 1);
+        assert_eq!(settings.lives, PLAYER_LIVES_MIN);
+        assert_eq!(settings.time_limit, ROUND_TIME_LIMIT_MAX);
+        assert_eq!(settings.geese_max, GOOSE_MAX_MAX);
+
+        let settings = MatchSettings::default();
+        assert_eq!(settings.clone().clamped(), settings);
+    }
+
+    #[test]
+    fn netcode_settings_from_disk_are_clamped() {
+        let netcode = NetcodeSettings {
+            input_delay: InputDelay::Fixed(100),
+            auto_input_delay: 100,
+            max_prediction: 0,
+            check_distance: 0,
+        }
+        .clamped();
+        assert_eq!(netcode.input_delay(), INPUT_DELAY_MAX);
+        assert_eq!(netcode.max_prediction, MAX_PREDICTION_MIN);
+        assert!(netcode.check_distance < netcode.max_prediction);
+
+        let netcode = NetcodeSettings {
+            check_distance: 0,
+            ..default()
+        }
+        .clamped();
+        assert_eq!(netcode.check_distance, CHECK_DISTANCE_MIN);
+    }
+}
@@ -1,31 +1,50 @@
 use bevy::prelude::*;
 use bevy_ggrs::{GgrsTime, Rollback, Session};
-use bevy_matchbox::{prelude::SingleChannel, MatchboxSocket};
+use bevy_matchbox::{
+    prelude::{MultipleChannels, PeerState},
+    MatchboxSocket,
+};
 
 use crate::{
-    menu::connect::LocalHandle,
+    menu::{connect::LocalHandle, lobby::LobbyRoom, online::PlayerCount},
     player::{
         components::EdibleSpawnTimer,
-        resources::{HealthBarsAdded, PlayersReady},
+        resources::{
+            HealthBarsAdded, MatchSettings, PlayerNames, PlayersReady, RoundScore, RoundTimer,
+        },
     },
+    stats::resources::RoundStats,
 };
 
 use super::{
     components::{Expired, RoundComponent},
+    edibles::EdibleRegistry,
     input::GGRSConfig,
+    network::{NetworkHud, NetworkNotices},
     resources::AgreedRandom,
 };
 
-pub fn setup_round(mut commands: Commands) {
+pub fn setup_round(
+    mut commands: Commands,
+    player_count: Res<PlayerCount>,
+    settings: Res<MatchSettings>,
+    registry: Res<EdibleRegistry>,
+) {
     trace!("setup_round");
 
     commands.spawn((Camera2dBundle::default(), RoundComponent));
-    commands.insert_resource(EdibleSpawnTimer::default());
+    commands.insert_resource(EdibleSpawnTimer::new(&registry));
+    commands.insert_resource(RoundScore::new(player_count.0));
+    commands.insert_resource(RoundStats::new(player_count.0));
+    commands.insert_resource(RoundTimer::new(settings.time_limit));
+    commands.insert_resource(NetworkNotices::default());
+    commands.insert_resource(NetworkHud::default());
 }
 
 pub fn disconnect_remote_players(
     mut session: ResMut<Session<GGRSConfig>>,
-    mut socket: ResMut<MatchboxSocket<SingleChannel>>,
+    mut socket: ResMut<MatchboxSocket<MultipleChannels>>,
+    room: Option<ResMut<LobbyRoom>>,
 ) {
     trace!("disconnecting remote players...");
     match session.as_mut() {
@@ -44,7 +63,15 @@ pub fn disconnect_remote_players(
         _ => (),
     }
     debug!("checking socket stats...");
-    socket.update_peers();
+    let changes = socket.update_peers();
+    // the lobby room has to know who left during the round before voting on a rematch
+    if let Some(mut room) = room {
+        for (peer, state) in changes {
+            if state == PeerState::Disconnected {
+                room.peers.retain(|p| *p != peer);
+            }
+        }
+    }
     for p in socket.connected_peers() {
         debug!("connected: {:?}", p)
     }
@@ -84,6 +111,9 @@ pub fn cleanup_session(mut commands: Commands, query: Query<Entity, With<Rollbac
     // cleanup local handle, local player could get a different handle next round
     commands.remove_resource::<LocalHandle>();
 
+    // names are exchanged again when the next session is set up
+    commands.insert_resource(PlayerNames::default());
+
     // finally remove old session
     commands.remove_resource::<Session<GGRSConfig>>();
 
@@ -93,6 +123,11 @@ pub fn cleanup_session(mut commands: Commands, query: Query<Entity, With<Rollbac
     // remove edible spawn timer, we will spawn a new one each round
     commands.remove_resource::<EdibleSpawnTimer>();
 
+    // remove round score, timer and stats, fresh ones are inserted each round
+    commands.remove_resource::<RoundScore>();
+    commands.remove_resource::<RoundTimer>();
+    commands.remove_resource::<RoundStats>();
+
     let mut targets = query.iter().collect::<Vec<_>>();
     targets.sort_by_key(|e| *e);
     debug!("number of rollback entities to remove: {:?}", targets.len());
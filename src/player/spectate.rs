@@ -0,0 +1,138 @@
+use bevy::{prelude::*, utils::HashMap};
+use bevy_ggrs::{ConfirmedFrameCount, LocalInputs, PlayerInputs, RollbackFrameCount};
+use ggrs::InputStatus;
+use std::time::Duration;
+
+use super::input::{GGRSConfig, PlayerInput};
+use super::network::NetworkNotices;
+use super::resources::{InputHistory, PlayerNames, Spectator};
+use crate::menu::connect::LocalHandle;
+use crate::FPS;
+
+/// Spectators this many frames behind the players fast forward
+pub const SPECTATOR_CATCH_UP_FRAMES: usize = 30;
+pub const SPECTATOR_CATCH_UP_SPEED: f32 = 8.0;
+/// Same as bevy's default, the most time the virtual clock advances in one update
+const SPECTATOR_MAX_DELTA: Duration = Duration::from_millis(250);
+
+/// Runs first in the rollback schedule. Players record the inputs of every simulated frame and
+/// commit it to the history once GGRS confirmed it, spectators restore the disconnects the
+/// players saw since their SyncTest session marks every input as confirmed.
+pub fn sync_input_history(
+    frame: Res<RollbackFrameCount>,
+    confirmed: Res<ConfirmedFrameCount>,
+    spectator: Option<Res<Spectator>>,
+    mut inputs: ResMut<PlayerInputs<GGRSConfig>>,
+    mut history: ResMut<InputHistory>,
+) {
+    // the frame count was already advanced to the frame these inputs produce
+    let index = (i32::from(*frame) - 1).max(0) as usize;
+
+    if spectator.is_some() {
+        let recorded = history.frames.get(index).or(history.frames.last());
+        for (handle, (_, disconnected)) in recorded.into_iter().flatten().enumerate() {
+            if *disconnected && handle < inputs.len() {
+                inputs[handle].1 = InputStatus::Disconnected;
+            }
+        }
+        return;
+    }
+
+    let frame_inputs = inputs
+        .iter()
+        .map(|(input, status)| (input.input, *status == InputStatus::Disconnected))
+        .collect();
+    history.record(index, frame_inputs, i32::from(*confirmed));
+}
+
+/// Reads the next frame's inputs from the history instead of the keyboard
+pub fn spectator_input(
+    mut commands: Commands,
+    frame: Res<RollbackFrameCount>,
+    history: Res<InputHistory>,
+) {
+    let index = i32::from(*frame).max(0) as usize;
+
+    // frames the players never confirmed before leaving the round are predicted from the
+    // last known inputs, just like GGRS would
+    let recorded = match history.frames.get(index) {
+        Some(recorded) => Some(recorded),
+        None if history.complete => history.frames.last(),
+        None => None,
+    };
+    if recorded.is_none() {
+        // the clock should have stopped before running out, SyncTest skips the frame
+        warn!("no inputs recorded for spectated frame {}", index);
+    }
+    let local_inputs = recorded
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .map(|(handle, (input, _))| (handle, PlayerInput { input: *input }))
+        .collect::<HashMap<_, _>>();
+
+    commands.insert_resource(LocalInputs::<GGRSConfig>(local_inputs));
+}
+
+/// GGRS advances with the virtual clock, stop it when we caught up with the players and
+/// run it faster while far behind. The delta is capped so no update runs past the last
+/// frame we have inputs for.
+pub fn pace_spectator_clock(
+    mut time: ResMut<Time<Virtual>>,
+    frame: Res<RollbackFrameCount>,
+    history: Res<InputHistory>,
+) {
+    let buffered = history
+        .frames
+        .len()
+        .saturating_sub(i32::from(*frame).max(0) as usize);
+
+    if buffered == 0 && history.complete {
+        time.unpause();
+        time.set_relative_speed(1.);
+        time.set_max_delta(SPECTATOR_MAX_DELTA);
+        return;
+    }
+    if buffered == 0 {
+        time.pause();
+        return;
+    }
+
+    let speed = if buffered > SPECTATOR_CATCH_UP_FRAMES {
+        SPECTATOR_CATCH_UP_SPEED
+    } else {
+        1.
+    };
+    // slightly less than the buffered frames so rounding never lets GGRS step one too far
+    let buffered_secs = buffered as f64 * 0.99 / FPS as f64 / speed as f64;
+    time.unpause();
+    time.set_relative_speed(speed);
+    time.set_max_delta(Duration::from_secs_f64(buffered_secs).min(SPECTATOR_MAX_DELTA));
+}
+
+pub fn announce_spectating(
+    time: Res<Time<Real>>,
+    names: Res<PlayerNames>,
+    handle: Res<LocalHandle>,
+    mut spectator: ResMut<Spectator>,
+    mut notices: ResMut<NetworkNotices>,
+) {
+    if spectator.announced {
+        return;
+    }
+    spectator.announced = true;
+    notices.push(
+        format!(
+            "Round in progress, spectating {} until the next one",
+            names.get(handle.0)
+        ),
+        time.elapsed_seconds_f64(),
+    );
+}
+
+/// Spectating changes the virtual clock, put it back for everyone leaving the round
+pub fn reset_spectator_clock(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+    time.set_relative_speed(1.);
+    time.set_max_delta(SPECTATOR_MAX_DELTA);
+}
@@ -0,0 +1,299 @@
+use bevy::prelude::*;
+use bevy_ggrs::{Rollback, RollbackFrameCount};
+use serde::{Deserialize, Serialize};
+
+use super::components::{DamageSource, Player};
+use super::events::DamageEvent;
+use super::resources::Spectator;
+use crate::loading::TextureAssets;
+use crate::menu::connect::LocalHandle;
+use crate::FPS;
+
+/// Most stacks a single stacking effect can build up
+pub const STATUS_STACKS_MAX: u32 = 5;
+/// Speed in percent of the normal speed while hasted or slowed
+pub const HASTE_SPEED_PERCENT: i32 = 150;
+pub const SLOW_SPEED_PERCENT: i32 = 60;
+/// Damage per stack every `POISON_INTERVAL` frames
+pub const POISON_DAMAGE: i32 = 1;
+pub const POISON_INTERVAL: u32 = FPS as u32;
+pub const BURNING_DAMAGE: i32 = 2;
+pub const BURNING_INTERVAL: u32 = FPS as u32 / 2;
+
+#[derive(Clone, Copy, Debug, Deserialize, Hash, PartialEq, Eq, Reflect, Serialize)]
+pub enum StatusKind {
+    /// Absorbs one hit per stack
+    Shield,
+    Haste,
+    Slow,
+    /// Damage over time, stacks
+    Poison,
+    /// Damage over time
+    Burning,
+    /// Hidden from other players
+    Invisibility,
+    /// Fireballs are shot as a spread of three
+    TripleShot,
+    /// Fireballs turn towards the nearest enemy
+    Homing,
+}
+
+/// How an effect combines with one of the same kind the player already has
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatusStacking {
+    /// Keeps a single stack and the longer of both durations
+    Refresh,
+    /// Adds the stacks up to the limit and restarts the duration
+    Stack(u32),
+}
+
+impl StatusKind {
+    /// HUD order
+    pub const ALL: [StatusKind; 8] = [
+        StatusKind::Shield,
+        StatusKind::TripleShot,
+        StatusKind::Homing,
+        StatusKind::Haste,
+        StatusKind::Slow,
+        StatusKind::Poison,
+        StatusKind::Burning,
+        StatusKind::Invisibility,
+    ];
+
+    pub fn stacking(&self) -> StatusStacking {
+        match self {
+            StatusKind::Shield | StatusKind::Poison => StatusStacking::Stack(STATUS_STACKS_MAX),
+            StatusKind::Haste
+            | StatusKind::Slow
+            | StatusKind::Burning
+            | StatusKind::Invisibility
+            | StatusKind::TripleShot
+            | StatusKind::Homing => StatusStacking::Refresh,
+        }
+    }
+
+    /// Letter drawn on the HUD icon
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            StatusKind::Shield => "S",
+            StatusKind::Haste => "H",
+            StatusKind::Slow => "L",
+            StatusKind::Poison => "P",
+            StatusKind::Burning => "B",
+            StatusKind::Invisibility => "I",
+            StatusKind::TripleShot => "3",
+            StatusKind::Homing => "@",
+        }
+    }
+
+    /// Power-ups use the texture of the edible granting them as their icon
+    pub fn texture(&self, textures: &TextureAssets) -> Option<Handle<Image>> {
+        match self {
+            StatusKind::Shield => Some(textures.texture_shell.clone()),
+            StatusKind::TripleShot => Some(textures.texture_ghost_pepper.clone()),
+            StatusKind::Homing => Some(textures.texture_blueberry.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            StatusKind::Shield => Color::CYAN,
+            StatusKind::Haste => Color::GOLD,
+            StatusKind::Slow => Color::SEA_GREEN,
+            StatusKind::Poison => Color::LIME_GREEN,
+            StatusKind::Burning => Color::ORANGE_RED,
+            StatusKind::Invisibility => Color::GRAY,
+            StatusKind::TripleShot => Color::ORANGE,
+            StatusKind::Homing => Color::BLUE,
+        }
+    }
+}
+
+/// A running effect, durations are counted in rollback frames so every peer expires it on
+/// the same frame
+#[derive(Clone, Debug, Hash, PartialEq, Reflect)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub frames_left: u32,
+    pub stacks: u32,
+    /// Handle of the player credited for damage over time
+    pub applied_by: Option<usize>,
+}
+
+/// Timed buffs and debuffs of a player
+#[derive(Clone, Component, Debug, Default, Hash, Reflect)]
+#[reflect(Component, Hash)]
+pub struct StatusEffects(pub Vec<StatusEffect>);
+
+impl StatusEffects {
+    pub fn get(&self, kind: StatusKind) -> Option<&StatusEffect> {
+        self.0.iter().find(|e| e.kind == kind)
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.get(kind).is_some()
+    }
+
+    pub fn apply(&mut self, kind: StatusKind, secs: f32, stacks: u32, applied_by: Option<usize>) {
+        let frames = (secs * FPS as f32).round() as u32;
+        let effect = match self.0.iter_mut().find(|e| e.kind == kind) {
+            Some(effect) => effect,
+            None => {
+                self.0.push(StatusEffect {
+                    kind,
+                    frames_left: frames,
+                    stacks: match kind.stacking() {
+                        StatusStacking::Refresh => 1,
+                        StatusStacking::Stack(max) => stacks.clamp(1, max),
+                    },
+                    applied_by,
+                });
+                return;
+            }
+        };
+
+        match kind.stacking() {
+            StatusStacking::Refresh => effect.frames_left = effect.frames_left.max(frames),
+            StatusStacking::Stack(max) => {
+                effect.stacks = (effect.stacks + stacks).min(max);
+                effect.frames_left = frames;
+            }
+        }
+        effect.applied_by = applied_by;
+    }
+
+    /// Uses up a shield stack, returns false when there was none to block the hit
+    pub fn absorb_hit(&mut self) -> bool {
+        let Some(index) = self.0.iter().position(|e| e.kind == StatusKind::Shield) else {
+            return false;
+        };
+        self.0[index].stacks -= 1;
+        if self.0[index].stacks == 0 {
+            self.0.remove(index);
+        }
+        true
+    }
+
+    /// Movement speed in percent, haste and slow cancel out when both are active
+    pub fn speed_percent(&self) -> i32 {
+        let mut percent = 100;
+        if self.has(StatusKind::Haste) {
+            percent = percent * HASTE_SPEED_PERCENT / 100;
+        }
+        if self.has(StatusKind::Slow) {
+            percent = percent * SLOW_SPEED_PERCENT / 100;
+        }
+        percent
+    }
+}
+
+/// Icon of one effect kind on the local player's HUD, hidden while the effect is inactive
+#[derive(Component)]
+pub struct StatusIcon(pub StatusKind);
+
+#[derive(Component)]
+pub struct StatusIconText(pub StatusKind);
+
+/// Counts down every effect and deals damage over time, dead players lose all effects
+pub fn tick_status_effects(
+    frame: Res<RollbackFrameCount>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut query: Query<(Entity, &Player, &mut StatusEffects), With<Rollback>>,
+) {
+    let mut players = query.iter_mut().collect::<Vec<_>>();
+    players.sort_by_key(|e| e.0);
+
+    for (_, player, mut effects) in players {
+        if !player.active {
+            effects.0.clear();
+            continue;
+        }
+
+        for effect in effects.0.iter_mut() {
+            effect.frames_left = effect.frames_left.saturating_sub(1);
+
+            let (source, damage, interval) = match effect.kind {
+                StatusKind::Poison => (
+                    DamageSource::Poison,
+                    POISON_DAMAGE * effect.stacks as i32,
+                    POISON_INTERVAL,
+                ),
+                StatusKind::Burning => (DamageSource::Burning, BURNING_DAMAGE, BURNING_INTERVAL),
+                _ => continue,
+            };
+            if effect.frames_left % interval == 0 {
+                damage_events.send(DamageEvent {
+                    // unattributed damage counts as self inflicted and is never credited
+                    attacker: effect.applied_by.unwrap_or(player.handle),
+                    victim: player.handle,
+                    source,
+                    amount: damage,
+                    frame: (*frame).into(),
+                });
+            }
+        }
+        effects.0.retain(|e| e.frames_left > 0);
+    }
+}
+
+/// Invisible players are only drawn for themselves, this is visual only. Spectators aren't
+/// any of the players, they see everyone with invisible players faded by `tint_player_sprites`.
+pub fn hide_invisible_players(
+    local_handle: Option<Res<LocalHandle>>,
+    spectator: Option<Res<Spectator>>,
+    mut query: Query<(&Player, &StatusEffects, &mut Visibility)>,
+) {
+    let local_handle = local_handle.map(|h| h.0);
+
+    for (player, effects, mut visibility) in query.iter_mut() {
+        let hidden = effects.has(StatusKind::Invisibility)
+            && spectator.is_none()
+            && Some(player.handle) != local_handle;
+        let wanted = if hidden {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+    }
+}
+
+pub fn update_status_effect_hud(
+    player_handle: Option<Res<LocalHandle>>,
+    player_query: Query<(&Player, &StatusEffects)>,
+    mut icon_query: Query<(&StatusIcon, &mut Style)>,
+    mut text_query: Query<(&StatusIconText, &mut Text)>,
+) {
+    let player_handle = match player_handle {
+        Some(handle) => handle.0,
+        None => return, // Session hasn't started yet
+    };
+    let Some((_, effects)) = player_query.iter().find(|(p, _)| p.handle == player_handle) else {
+        return;
+    };
+
+    for (icon, mut style) in icon_query.iter_mut() {
+        let display = if effects.has(icon.0) {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        if style.display != display {
+            style.display = display;
+        }
+    }
+    for (icon, mut text) in text_query.iter_mut() {
+        let Some(effect) = effects.get(icon.0) else {
+            continue;
+        };
+        let secs = effect.frames_left as f32 / FPS as f32;
+        text.sections[0].value = if effect.stacks > 1 {
+            format!("x{} {:.1}s", effect.stacks, secs)
+        } else {
+            format!("{:.1}s", secs)
+        };
+    }
+}
@@ -2,44 +2,60 @@ use std::time::Duration;
 
 use super::checksum::Checksum;
 use super::components::{
-    Edible, EdibleSpawnTimer, Fireball, FireballAmmo, FireballMovement, FireballReady,
-    FireballTimer, Player, PlayerFireballText, PlayerHealth, PlayerHealthBar, PlayerHealthText,
-    PlayerPoop, PlayerPoopTimer, PlayerSpeed, PlayerSpeedBoost, PlayerSpeedBoostText,
-    RoundComponent, CHILI_PEPPER_AMMO_COUNT, CHILI_PEPPER_SIZE, FIREBALL_DAMAGE, FIREBALL_RADIUS,
-    LETTUCE_HEALTH_GAIN, LETTUCE_SIZE, PLAYER_HEALTH_LOW, PLAYER_HEALTH_MAX, PLAYER_HEALTH_MID,
+    BashCooldown, DamageSource, Edible, EdibleSpawnTimer, FireballAmmo, FireballReady,
+    KillFeedEntry, KillFeedText, LastHitBy, NetworkHudRow, NetworkHudText, NetworkNoticeText,
+    NetworkQualityIcon, NetworkWarningText, Player, PlayerFireballText, PlayerHealth,
+    PlayerHealthBar, PlayerHealthText, PlayerInvulnerable, PlayerLives, PlayerLivesText,
+    PlayerNameTag, PlayerPoop, PlayerPoopText, PlayerPoopTimer, PlayerRespawnTimer,
+    PlayerScoreText, PlayerSpeed, PlayerSpeedBoost, PlayerSpeedBoostText, PlayerVelocity, PoopAmmo,
+    PoopReady, RoundComponent, RoundTimerText, BASH_DAMAGE, BASH_KNOCKBACK, BASH_RANGE,
+    FIREBALL_SPREAD_COS, FIREBALL_SPREAD_SIN, KILL_FEED_LIFETIME, KILL_FEED_MAX_LINES,
+    KNOCKBACK_DECAY, KNOCKBACK_MIN_SPEED, PLAYER_HEALTH_LOW, PLAYER_HEALTH_MAX, PLAYER_HEALTH_MID,
     PLAYER_SPEED_BOOST, PLAYER_SPEED_BOOST_MAX, PLAYER_SPEED_MAX, PLAYER_SPEED_START, POOP_DAMAGE,
-    POOP_ENTITIES_MAX, POOP_SIZE, STRAWBERRY_AMMO_COUNT, STRAWBERRY_SIZE,
+    POOP_DROP_DISTANCE, POOP_PER_PLAYER_MAX, POOP_SIZE, POOP_SLIP_TIME,
 };
+use super::edibles::{EdibleDef, EdibleEffect, EdibleRegistry, SpawnZone};
+use super::events::DamageEvent;
 use super::input::{
-    GGRSConfig, PlayerControls, INPUT_DOWN, INPUT_EXIT, INPUT_FIRE, INPUT_LEFT, INPUT_RIGHT,
-    INPUT_SPRINT, INPUT_UP,
+    GGRSConfig, PlayerControls, INPUT_BASH, INPUT_DOWN, INPUT_EXIT, INPUT_FIRE, INPUT_LEFT,
+    INPUT_POOP, INPUT_RIGHT, INPUT_SPRINT, INPUT_UP,
 };
-use super::resources::{AgreedRandom, HealthBarsAdded};
+use super::projectiles::{spawn_projectile, Projectile, ProjectileHoming, ProjectileKind};
+use super::resources::{
+    AgreedRandom, GameMode, HealthBarsAdded, MatchSettings, PlayerNames, RoundScore, RoundTimer,
+    Spectator,
+};
+use super::status::{StatusEffects, StatusIcon, StatusIconText, StatusKind};
 
 use crate::audio::{FadedLoopSound, RollbackSound, RollbackSoundBundle};
 use crate::graphics::{CharacterSheet, FrameAnimation};
 use crate::loading::{AudioAssets, FontAssets, TextureAssets};
 use crate::map::tilemap::{EncounterSpawner, PlayerSpawn, TileCollider};
 use crate::menu::connect::LocalHandle;
-use crate::menu::online::PlayerCount;
-use crate::menu::win::MatchData;
+use crate::menu::online::{PlayerCount, MAX_PLAYERS};
+use crate::menu::win::{MatchData, MatchResults};
+use crate::npc::components::Goose;
+use crate::npc::events::GooseHitEvent;
+use crate::physics::{Fixed, FixedVec2, Position};
 use crate::player::components::Expired;
 use crate::player::resources::PlayersReady;
-use crate::{AppState, FIXED_TICK_MS, FPS, HEALTH_BAR_Y_OFFSET};
+use crate::stats::resources::RoundStats;
+use crate::{AppState, FIXED_TICK_MS, FPS, HEALTH_BAR_Y_OFFSET, NAME_TAG_Y_OFFSET};
 use crate::{GameState, TILE_SIZE};
 use bevy::core::FrameCount;
 use bevy::math::vec3;
 use bevy::prelude::*;
-use bevy::sprite::collide_aabb::collide;
-use bevy_ggrs::Rollback;
 use bevy_ggrs::{AddRollbackCommandExtension, PlayerInputs};
+use bevy_ggrs::{Rollback, RollbackFrameCount};
 use ggrs::InputStatus;
 use rand::Rng;
 
 pub fn create_ui(
     mut commands: Commands,
     font_assets: Res<FontAssets>,
+    textures: Res<TextureAssets>,
     player_handle: Option<Res<LocalHandle>>,
+    names: Res<PlayerNames>,
 ) {
     trace!("create_ui");
 
@@ -48,7 +64,7 @@ pub fn create_ui(
         None => return, // Session hasn't started yet
     };
 
-    let player_name = format!("Player {}", player_handle);
+    let player_name = names.get(player_handle);
 
     // root node
     commands
@@ -107,6 +123,19 @@ pub fn create_ui(
                     ..Default::default()
                 })
                 .insert(PlayerFireballText);
+            parent
+                .spawn(TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: font_assets.fira_sans.clone(),
+                            font_size: 40.0,
+                            color: Color::GOLD,
+                        },
+                    ),
+                    ..Default::default()
+                })
+                .insert(PlayerPoopText);
             parent
                 .spawn(TextBundle {
                     text: Text::from_section(
@@ -120,15 +149,280 @@ pub fn create_ui(
                     ..Default::default()
                 })
                 .insert(PlayerSpeedBoostText);
+            parent
+                .spawn(TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: font_assets.fira_sans.clone(),
+                            font_size: 40.0,
+                            color: Color::GOLD,
+                        },
+                    ),
+                    ..Default::default()
+                })
+                .insert(PlayerLivesText);
+            parent
+                .spawn(TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: font_assets.fira_sans.clone(),
+                            font_size: 40.0,
+                            color: Color::GOLD,
+                        },
+                    ),
+                    ..Default::default()
+                })
+                .insert(PlayerScoreText);
+            parent
+                .spawn(TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: font_assets.fira_sans.clone(),
+                            font_size: 40.0,
+                            color: Color::GOLD,
+                        },
+                    ),
+                    ..Default::default()
+                })
+                .insert(RoundTimerText);
+            // active status effects, one icon per kind
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Start,
+                        ..Default::default()
+                    },
+                    background_color: BackgroundColor(Color::NONE),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    for kind in StatusKind::ALL {
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    display: Display::None,
+                                    flex_direction: FlexDirection::Row,
+                                    align_items: AlignItems::Center,
+                                    margin: UiRect::top(Val::Px(4.)),
+                                    ..Default::default()
+                                },
+                                background_color: BackgroundColor(Color::NONE),
+                                ..Default::default()
+                            })
+                            .with_children(|parent| {
+                                parent
+                                    .spawn(NodeBundle {
+                                        style: Style {
+                                            width: Val::Px(24.),
+                                            height: Val::Px(24.),
+                                            margin: UiRect::right(Val::Px(6.)),
+                                            justify_content: JustifyContent::Center,
+                                            align_items: AlignItems::Center,
+                                            ..Default::default()
+                                        },
+                                        background_color: BackgroundColor(kind.color()),
+                                        ..Default::default()
+                                    })
+                                    .with_children(|parent| match kind.texture(&textures) {
+                                        // power-ups show the edible that granted them
+                                        Some(texture) => {
+                                            parent.spawn(ImageBundle {
+                                                style: Style {
+                                                    width: Val::Px(20.),
+                                                    height: Val::Px(20.),
+                                                    ..Default::default()
+                                                },
+                                                image: UiImage::new(texture),
+                                                ..Default::default()
+                                            });
+                                        }
+                                        None => {
+                                            parent.spawn(TextBundle {
+                                                text: Text::from_section(
+                                                    kind.symbol(),
+                                                    TextStyle {
+                                                        font: font_assets.fira_sans.clone(),
+                                                        font_size: 18.0,
+                                                        color: Color::BLACK,
+                                                    },
+                                                ),
+                                                ..Default::default()
+                                            });
+                                        }
+                                    });
+                                parent
+                                    .spawn(TextBundle {
+                                        text: Text::from_section(
+                                            "",
+                                            TextStyle {
+                                                font: font_assets.fira_sans.clone(),
+                                                font_size: 24.0,
+                                                color: Color::GOLD,
+                                            },
+                                        ),
+                                        ..Default::default()
+                                    })
+                                    .insert(StatusIconText(kind));
+                            })
+                            .insert(StatusIcon(kind));
+                    }
+                });
         })
         .insert(RoundComponent)
         .insert(Name::new("PlayerUI"));
+
+    // kill feed node
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(10.),
+                right: Val::Auto,
+                top: Val::Auto,
+                bottom: Val::Px(10.),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Start,
+                ..Default::default()
+            },
+            background_color: BackgroundColor(Color::NONE),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: font_assets.fira_sans.clone(),
+                            font_size: 30.0,
+                            color: Color::ORANGE_RED,
+                        },
+                    ),
+                    ..Default::default()
+                })
+                .insert(KillFeedText);
+        })
+        .insert(RoundComponent)
+        .insert(Name::new("KillFeedUI"));
+
+    // network notice node
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(0.),
+                right: Val::Px(0.),
+                top: Val::Px(10.),
+                bottom: Val::Auto,
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            background_color: BackgroundColor(Color::NONE),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: font_assets.fira_sans.clone(),
+                            font_size: 30.0,
+                            color: Color::YELLOW,
+                        },
+                    ),
+                    ..Default::default()
+                })
+                .insert(NetworkNoticeText);
+        })
+        .insert(RoundComponent)
+        .insert(Name::new("NetworkNoticeUI"));
+
+    // network quality hud, one row per remote peer
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(10.),
+                right: Val::Auto,
+                top: Val::Px(10.),
+                bottom: Val::Auto,
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Start,
+                ..Default::default()
+            },
+            background_color: BackgroundColor(Color::NONE),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            for i in 0..MAX_PLAYERS - 1 {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            display: Display::None,
+                            flex_direction: FlexDirection::Row,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        background_color: BackgroundColor(Color::NONE),
+                        ..Default::default()
+                    })
+                    .with_children(|parent| {
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    width: Val::Px(12.),
+                                    height: Val::Px(12.),
+                                    margin: UiRect::right(Val::Px(6.)),
+                                    ..Default::default()
+                                },
+                                background_color: BackgroundColor(Color::GRAY),
+                                ..Default::default()
+                            })
+                            .insert(NetworkQualityIcon(i));
+                        parent
+                            .spawn(TextBundle {
+                                text: Text::from_section(
+                                    "",
+                                    TextStyle {
+                                        font: font_assets.fira_sans.clone(),
+                                        font_size: 18.0,
+                                        color: Color::WHITE,
+                                    },
+                                ),
+                                ..Default::default()
+                            })
+                            .insert(NetworkHudText(i));
+                    })
+                    .insert(NetworkHudRow(i));
+            }
+            parent
+                .spawn(TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: font_assets.fira_sans.clone(),
+                            font_size: 22.0,
+                            color: Color::ORANGE,
+                        },
+                    ),
+                    ..Default::default()
+                })
+                .insert(NetworkWarningText);
+        })
+        .insert(RoundComponent)
+        .insert(Name::new("NetworkHudUI"));
 }
 
 pub fn update_player_health_text(
     player_handle: Option<Res<LocalHandle>>,
     mut text_query: Query<&mut Text, With<PlayerHealthText>>,
-    player_query: Query<(&Player, &PlayerHealth), Without<Fireball>>,
+    player_query: Query<(&Player, &PlayerHealth), Without<Projectile>>,
 ) {
     let player_handle = match player_handle {
         Some(handle) => handle.0,
@@ -159,7 +453,7 @@ pub fn update_player_health_text(
 pub fn update_player_fireball_text(
     player_handle: Option<Res<LocalHandle>>,
     mut text_query: Query<&mut Text, With<PlayerFireballText>>,
-    player_query: Query<(&Player, &FireballAmmo), Without<Fireball>>,
+    player_query: Query<(&Player, &FireballAmmo), Without<Projectile>>,
 ) {
     let player_handle = match player_handle {
         Some(handle) => handle.0,
@@ -178,10 +472,32 @@ pub fn update_player_fireball_text(
     }
 }
 
+pub fn update_player_poop_text(
+    player_handle: Option<Res<LocalHandle>>,
+    mut text_query: Query<&mut Text, With<PlayerPoopText>>,
+    player_query: Query<(&Player, &PoopAmmo), Without<Projectile>>,
+) {
+    let player_handle = match player_handle {
+        Some(handle) => handle.0,
+        None => return, // Session hasn't started yet
+    };
+
+    for (player, ammo) in player_query.iter() {
+        if player.handle != player_handle {
+            continue;
+        }
+
+        for mut text in text_query.iter_mut() {
+            let val = format!("Poops: {}", ammo.0);
+            text.sections[0].value = val;
+        }
+    }
+}
+
 pub fn update_player_speed_boost_text(
     player_handle: Option<Res<LocalHandle>>,
     mut text_query: Query<&mut Text, With<PlayerSpeedBoostText>>,
-    player_query: Query<(&Player, &PlayerSpeedBoost), Without<Fireball>>,
+    player_query: Query<(&Player, &PlayerSpeedBoost), Without<Projectile>>,
 ) {
     let player_handle = match player_handle {
         Some(handle) => handle.0,
@@ -200,9 +516,96 @@ pub fn update_player_speed_boost_text(
     }
 }
 
+pub fn update_player_lives_text(
+    player_handle: Option<Res<LocalHandle>>,
+    mut text_query: Query<&mut Text, With<PlayerLivesText>>,
+    player_query: Query<(&Player, &PlayerLives), Without<Projectile>>,
+) {
+    let player_handle = match player_handle {
+        Some(handle) => handle.0,
+        None => return, // Session hasn't started yet
+    };
+
+    for (player, lives) in player_query.iter() {
+        if player.handle != player_handle {
+            continue;
+        }
+
+        for mut text in text_query.iter_mut() {
+            let val = format!("Lives: {}", lives.0);
+            text.sections[0].value = val;
+        }
+    }
+}
+
+pub fn update_player_score_text(
+    player_handle: Option<Res<LocalHandle>>,
+    score: Option<Res<RoundScore>>,
+    mut text_query: Query<&mut Text, With<PlayerScoreText>>,
+) {
+    let player_handle = match player_handle {
+        Some(handle) => handle.0,
+        None => return, // Session hasn't started yet
+    };
+    let score = match score {
+        Some(score) => score,
+        None => return, // Round hasn't started yet
+    };
+
+    for mut text in text_query.iter_mut() {
+        let val = format!(
+            "Kills: {}",
+            score.kills.get(player_handle).copied().unwrap_or_default()
+        );
+        text.sections[0].value = val;
+    }
+}
+
+pub fn update_round_timer_text(
+    round_timer: Option<Res<RoundTimer>>,
+    mut text_query: Query<&mut Text, With<RoundTimerText>>,
+) {
+    let round_timer = match round_timer {
+        Some(timer) => timer,
+        None => return, // Round hasn't started yet
+    };
+
+    let remaining = round_timer.remaining().as_secs();
+    for mut text in text_query.iter_mut() {
+        let val = format!("Time: {}:{:02}", remaining / 60, remaining % 60);
+        text.sections[0].value = val;
+    }
+}
+
+pub fn update_kill_feed_text(
+    frame: Res<RollbackFrameCount>,
+    names: Res<PlayerNames>,
+    mut text_query: Query<&mut Text, With<KillFeedText>>,
+    entry_query: Query<&KillFeedEntry>,
+) {
+    let frame = i32::from(*frame);
+    let mut entries = entry_query
+        .iter()
+        .filter(|e| frame - e.frame < KILL_FEED_LIFETIME)
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|e| (e.frame, e.victim));
+
+    let lines = entries
+        .iter()
+        .rev()
+        .take(KILL_FEED_MAX_LINES)
+        .rev()
+        .map(|e| e.message(&names))
+        .collect::<Vec<_>>();
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = lines.join("\n");
+    }
+}
+
 pub fn camera_follow(
     player_handle: Option<Res<LocalHandle>>,
-    player_query: Query<(&Transform, &Player), Without<Fireball>>,
+    player_query: Query<(&Transform, &Player), Without<Projectile>>,
     mut camera_query: Query<&mut Transform, (Without<Player>, With<Camera>)>,
 ) {
     // todo: follow another player when local player dies
@@ -225,11 +628,15 @@ pub fn camera_follow(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_players(
     mut commands: Commands,
     sounds: Res<AudioAssets>,
     characters: Res<CharacterSheet>,
+    font_assets: Res<FontAssets>,
     player_count: Res<PlayerCount>,
+    names: Res<PlayerNames>,
+    settings: Res<MatchSettings>,
     spawn_query: Query<&mut PlayerSpawn>,
     local_handle: Option<Res<LocalHandle>>,
 ) {
@@ -247,19 +654,18 @@ pub fn spawn_players(
     sprite.custom_size = Some(Vec2::splat(TILE_SIZE * 2.));
 
     for handle in 0..player_count.0 {
-        let name = format!("Player {}", handle);
+        let name = names.get(handle);
+        let pos = spawns[handle].pos;
         let player_id = commands
             .spawn((
-                Name::new(name),
+                Name::new(name.clone()),
                 SpriteSheetBundle {
                     sprite: sprite.clone(),
                     texture_atlas: characters.turtle_handle.clone(),
-                    transform: Transform {
-                        translation: Vec3::new(spawns[handle].pos.x, spawns[handle].pos.y, 1.),
-                        ..Default::default()
-                    },
+                    transform: Transform::from_translation(pos.extend(1.)),
                     ..Default::default()
                 },
+                Position(pos),
                 FrameAnimation {
                     timer: Timer::from_seconds(0.2, TimerMode::Repeating),
                     frames: characters.turtle_frames.to_vec(),
@@ -269,16 +675,47 @@ pub fn spawn_players(
                     handle,
                     ..Default::default()
                 },
-                FireballAmmo::default(),
-                FireballReady::default(),
+                // bevy bundles hold at most 15 items, ammo is grouped
+                (
+                    FireballAmmo::default(),
+                    FireballReady::default(),
+                    PoopAmmo::default(),
+                    PoopReady::default(),
+                ),
                 PlayerControls::default(),
                 PlayerHealth::default(),
-                PlayerSpeed::default(),
-                PlayerSpeedBoost::default(),
+                PlayerLives(settings.lives),
+                LastHitBy::default(),
+                // movement state is grouped for the same reason
+                (
+                    PlayerSpeed::default(),
+                    PlayerSpeedBoost::default(),
+                    PlayerVelocity::default(),
+                ),
+                StatusEffects::default(),
                 Checksum::default(),
                 RoundComponent,
             ))
             .add_rollback()
+            .with_children(|cb| {
+                cb.spawn(Text2dBundle {
+                    text: Text::from_section(
+                        name,
+                        TextStyle {
+                            font: font_assets.fira_sans.clone(),
+                            font_size: 14.,
+                            color: if handle == local_handle {
+                                Color::GOLD
+                            } else {
+                                Color::WHITE
+                            },
+                        },
+                    ),
+                    transform: Transform::from_xyz(0., NAME_TAG_Y_OFFSET, 0.3),
+                    ..default()
+                })
+                .insert(PlayerNameTag);
+            })
             .id();
 
         if handle == local_handle {
@@ -297,7 +734,7 @@ pub fn spawn_players(
 
 pub fn set_walking_sound(mut query: Query<(&mut FadedLoopSound, &PlayerControls)>) {
     for (mut sound, controls) in query.iter_mut() {
-        if controls.dir == Vec2::ZERO {
+        if controls.dir == FixedVec2::ZERO {
             sound.should_play = false
         } else {
             sound.should_play = true
@@ -316,22 +753,22 @@ pub fn apply_inputs(
             InputStatus::Disconnected => 0, // disconnected players do nothing
         };
 
-        let mut direction = Vec2::ZERO;
+        let mut direction = FixedVec2::ZERO;
         if input & INPUT_UP != 0 {
-            direction.y += 1.;
+            direction.y += Fixed::ONE;
         }
         if input & INPUT_DOWN != 0 {
-            direction.y -= 1.;
+            direction.y -= Fixed::ONE;
         }
         if input & INPUT_RIGHT != 0 {
-            direction.x += 1.;
+            direction.x += Fixed::ONE;
         }
         if input & INPUT_LEFT != 0 {
-            direction.x -= 1.;
+            direction.x -= Fixed::ONE;
         }
         pc.dir = direction.normalize_or_zero();
 
-        if direction != Vec2::ZERO {
+        if direction != FixedVec2::ZERO {
             pc.last_dir = pc.dir;
         }
 
@@ -340,6 +777,16 @@ pub fn apply_inputs(
         } else {
             pc.shooting = false;
         }
+        if input & INPUT_BASH != 0 {
+            pc.bashing = true;
+        } else {
+            pc.bashing = false;
+        }
+        if input & INPUT_POOP != 0 {
+            pc.pooping = true;
+        } else {
+            pc.pooping = false;
+        }
         if input & INPUT_SPRINT != 0 {
             pc.sprinting = true;
         } else {
@@ -373,13 +820,16 @@ pub fn apply_player_sprint(
 }
 
 pub fn move_players(
-    walls: Query<&Transform, (With<TileCollider>, Without<Player>)>,
+    mut stats: ResMut<RoundStats>,
+    walls: Query<&Position, (With<TileCollider>, Without<Player>)>,
     mut query: Query<
         (
-            &mut Transform,
+            &mut Position,
             &mut TextureAtlasSprite,
             &mut Player,
+            &mut PlayerVelocity,
             &PlayerSpeed,
+            &StatusEffects,
             &PlayerControls,
         ),
         With<Rollback>,
@@ -391,120 +841,179 @@ pub fn move_players(
 
     // loop over all players and apply their inputs to movement
     // do NOT return early because we need to check all players for input/movement
-    for (mut transform, mut sprite, player, speed, controls) in players {
+    for (mut position, mut sprite, player, mut velocity, speed, effects, controls) in players {
         if !player.active {
+            velocity.0 = FixedVec2::ZERO;
             continue; // don't return, we need to check other players for movement
         }
 
-        let movement = (controls.dir * speed.0 as f32 / FPS as f32).extend(0.);
-        let target = transform.translation + Vec3::new(0.0, movement.y, 0.0);
+        let start = position.0;
+        let speed = speed.0 * effects.speed_percent() / 100;
+        // knockback is added on top of the player's own movement, a wall stops it on that axis
+        let movement = (controls.dir * speed + velocity.0) / FPS as i32;
+        let target = position.0 + FixedVec2::new(Fixed::ZERO, movement.y);
         if !walls
             .iter()
-            .any(|&transform| wall_collision_check(target, transform.translation))
+            .any(|wall| wall_collision_check(target, wall.0))
         {
-            transform.translation = target;
+            position.0 = target;
+        } else {
+            velocity.0.y = Fixed::ZERO;
         }
 
-        let target = transform.translation + Vec3::new(movement.x, 0.0, 0.0);
+        let target = position.0 + FixedVec2::new(movement.x, Fixed::ZERO);
         if !walls
             .iter()
-            .any(|&transform| wall_collision_check(target, transform.translation))
+            .any(|wall| wall_collision_check(target, wall.0))
         {
-            if movement.x != 0.0 {
-                if movement.x > 0.0 {
+            if controls.dir.x != Fixed::ZERO {
+                if controls.dir.x > Fixed::ZERO {
                     sprite.flip_x = false;
                 } else {
                     sprite.flip_x = true;
                 }
             }
-            transform.translation = target;
+            position.0 = target;
+        } else {
+            velocity.0.x = Fixed::ZERO;
+        }
+
+        velocity.0 *= KNOCKBACK_DECAY;
+        if velocity.0.length() < KNOCKBACK_MIN_SPEED {
+            velocity.0 = FixedVec2::ZERO;
         }
+
+        stats.record_distance(player.handle, start.distance(position.0).to_f32());
     }
 }
 
-pub fn wall_collision_check(target_player_pos: Vec3, wall_translation: Vec3) -> bool {
-    let collision = collide(
-        target_player_pos,
-        Vec2::splat(TILE_SIZE * 0.9), // give player small amount of leeway
-        wall_translation,
-        Vec2::splat(TILE_SIZE),
-    );
-    collision.is_some()
+const TILE: Fixed = Fixed::from_f32(TILE_SIZE);
+/// Players are a bit smaller than a tile so they fit through one tile wide gaps
+const PLAYER_WALL_REACH: Fixed = Fixed::from_f32((TILE_SIZE * 0.9 + TILE_SIZE) / 2.);
+
+pub fn wall_collision_check(target_player_pos: FixedVec2, wall_pos: FixedVec2) -> bool {
+    let offset = target_player_pos - wall_pos;
+    offset.x.abs() < PLAYER_WALL_REACH && offset.y.abs() < PLAYER_WALL_REACH
 }
 
+/// Drops poop behind the turtle, one per press of INPUT_POOP while the player has ammo and
+/// fewer than `POOP_PER_PLAYER_MAX` poops on the map
 pub fn player_poops(
     mut commands: Commands,
-    frame: Res<FrameCount>,
-    sounds: Res<AudioAssets>,
     textures: Res<TextureAssets>,
-    player_query: Query<(&PlayerControls, &Transform, &PlayerSpeedBoost, &Player)>,
-    poop_query: Query<&PlayerPoop>,
+    mut player_query: Query<(
+        Entity,
+        &PlayerControls,
+        &Position,
+        &mut PoopAmmo,
+        &mut PoopReady,
+        &Player,
+    )>,
+    poop_query: Query<&PlayerPoop, Without<Expired>>,
 ) {
-    let poop_count = poop_query.iter().len();
-    for (controls, transform, boost, player) in player_query.iter() {
-        if controls.sprinting && boost.0 > 0 && poop_count < POOP_ENTITIES_MAX {
-            let player_pos = transform.translation;
-            let pos = player_pos
-                + (Vec3::new(controls.dir.x, controls.dir.y, 0.)) / (TILE_SIZE * 1.5)
-                + POOP_SIZE;
-
-            let poop_instance = commands
-                .spawn((
-                    Name::new("PlayerPoop"),
-                    PlayerPoop {
-                        shat_by: player.handle,
-                    },
-                    PlayerPoopTimer::default(),
-                    RoundComponent,
-                    SpriteBundle {
-                        sprite: Sprite {
-                            ..Default::default()
-                        },
-                        transform: Transform::from_xyz(pos.x, pos.y, 1.0)
-                            .with_rotation(Quat::from_rotation_arc_2d(Vec2::X, controls.last_dir)),
-                        texture: textures.texture_poop.clone(),
-                        ..Default::default()
-                    },
-                ))
-                .add_rollback()
-                .id();
+    let mut players = player_query.iter_mut().collect::<Vec<_>>();
+    players.sort_by_key(|p| p.0);
 
-            // spawn desired audio clip
-            commands
-                .spawn(RollbackSoundBundle {
-                    sound: RollbackSound {
-                        clip: sounds.sprinting.clone(),
-                        start_frame: frame.0,
-                        sub_key: poop_instance.index(),
-                    },
-                })
-                .add_rollback();
+    for (_, controls, position, mut ammo, mut ready, player) in players {
+        // holding the button drops a single poop
+        if !controls.pooping {
+            ready.0 = ammo.0 > 0;
+            continue;
         }
-    }
-}
-
-// TODO: add sound
-pub fn player_stepped_in_poop(
-    mut commands: Commands,
-    mut player_query: Query<(&Transform, &mut PlayerHealth, &Player)>,
-    poop_query: Query<(Entity, &Transform, &PlayerPoop), (With<Rollback>, Without<Expired>)>,
-) {
-    for (player_transform, mut health, player) in player_query.iter_mut() {
-        for (poop_ent, poop_transform, poop) in poop_query.iter() {
-            if poop.shat_by == player.handle {
-                continue;
-            }
-            let distance = player_transform
-                .translation
-                .distance(poop_transform.translation);
-
-            if distance < TILE_SIZE / 2.0 + POOP_SIZE / 2.0 {
-                // stepped in shit, take a little damage
-                health.0 -= POOP_DAMAGE;
-                commands.entity(poop_ent).insert(Expired);
-            }
+        if !player.active || !ready.0 || ammo.0 == 0 {
+            continue;
         }
-    }
+        let dropped = poop_query
+            .iter()
+            .filter(|p| p.shat_by == player.handle)
+            .count();
+        if dropped >= POOP_PER_PLAYER_MAX {
+            continue;
+        }
+
+        let pos = position.0 - controls.last_dir * Fixed::from_f32(POOP_DROP_DISTANCE);
+
+        commands
+            .spawn((
+                Name::new("PlayerPoop"),
+                PlayerPoop {
+                    shat_by: player.handle,
+                },
+                PlayerPoopTimer::default(),
+                RoundComponent,
+                Position(pos),
+                SpriteBundle {
+                    transform: Transform::from_translation(pos.extend(1.0)).with_rotation(
+                        Quat::from_rotation_arc_2d(Vec2::X, controls.last_dir.to_vec2()),
+                    ),
+                    texture: textures.texture_poop.clone(),
+                    ..Default::default()
+                },
+            ))
+            .add_rollback();
+        ammo.0 -= 1;
+        ready.0 = false;
+    }
+}
+
+type LivePoop = (With<Rollback>, Without<Expired>);
+
+/// Stepping in someone else's poop hurts a little and makes the player slip, slowing them down.
+/// A poop is used up by the first player stepping in it.
+pub fn player_stepped_in_poop(
+    mut commands: Commands,
+    frame: Res<RollbackFrameCount>,
+    sound_frame: Res<FrameCount>,
+    sounds: Res<AudioAssets>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut player_query: Query<
+        (Entity, &Position, &Player, &mut StatusEffects),
+        Without<PlayerInvulnerable>,
+    >,
+    poop_query: Query<(Entity, &Position, &PlayerPoop), LivePoop>,
+) {
+    // collect and sort all players and poops in play so we damage players in a deterministic order
+    let mut players = player_query.iter_mut().collect::<Vec<_>>();
+    players.sort_by_key(|e| e.0);
+
+    let mut poops = poop_query.iter().collect::<Vec<_>>();
+    poops.sort_by_key(|e| e.0);
+
+    for (poop_ent, poop_pos, poop) in poops {
+        // dead players and the one who dropped it can't step in it,
+        // ties go to the first player in entity order
+        let victim = players.iter_mut().find(|(_, player_pos, player, _)| {
+            player.active
+                && poop.shat_by != player.handle
+                && player_pos.0.distance(poop_pos.0)
+                    < Fixed::from_f32(TILE_SIZE / 2.0 + POOP_SIZE / 2.0)
+        });
+        let Some((_, _, player, effects)) = victim else {
+            continue;
+        };
+
+        // stepped in shit, take a little damage and slip
+        damage_events.send(DamageEvent {
+            attacker: poop.shat_by,
+            victim: player.handle,
+            source: DamageSource::Poop,
+            amount: POOP_DAMAGE,
+            frame: (*frame).into(),
+        });
+        effects.apply(StatusKind::Slow, POOP_SLIP_TIME, 1, Some(poop.shat_by));
+        commands.entity(poop_ent).insert(Expired);
+
+        // spawn desired audio clip
+        commands
+            .spawn(RollbackSoundBundle {
+                sound: RollbackSound {
+                    clip: sounds.poop_slip.clone(),
+                    start_frame: sound_frame.0,
+                    sub_key: poop_ent.index(),
+                },
+            })
+            .add_rollback();
+    }
 }
 
 pub fn tick_poop_timers(mut query: Query<(Entity, &mut PlayerPoopTimer), Without<Expired>>) {
@@ -535,291 +1044,180 @@ pub fn despawn_old_poops(
 }
 
 pub fn tick_edible_timer(mut edible_spawn_timer: ResMut<EdibleSpawnTimer>) {
-    edible_spawn_timer
-        .chili_pepper_timer
-        .tick(Duration::from_millis(FIXED_TICK_MS));
-    edible_spawn_timer
-        .strawberry_timer
-        .tick(Duration::from_millis(FIXED_TICK_MS));
-    edible_spawn_timer
-        .lettuce_timer
-        .tick(Duration::from_millis(FIXED_TICK_MS));
+    for timer in edible_spawn_timer.timers.iter_mut() {
+        timer.tick(Duration::from_millis(FIXED_TICK_MS));
+    }
 }
 
-pub fn spawn_strawberry_over_time(
-    mut commands: Commands,
-    mut agreed_seed: ResMut<AgreedRandom>, // TODO: why does this need mut?
-    asset_server: Res<TextureAssets>,
-    timer: Res<EdibleSpawnTimer>,
-    spawner_query: Query<&Transform, With<EncounterSpawner>>,
-) {
-    if timer.strawberry_timer.finished() {
-        let spawn_area: Vec<&Transform> = spawner_query.iter().collect();
-
-        let idx = agreed_seed.rng.gen_range(0..spawn_area.len());
-        let pos = spawn_area[idx];
-
-        commands
-            .spawn((
-                Name::new("Strawberry"),
-                Edible::Strawberry,
-                RoundComponent,
-                SpriteBundle {
-                    transform: Transform::from_xyz(pos.translation.x, pos.translation.y, 1.0),
-                    texture: asset_server.texture_strawberry.clone(),
+pub fn spawn_edible(commands: &mut Commands, kind: usize, def: &EdibleDef, pos: FixedVec2) {
+    commands
+        .spawn((
+            Name::new(def.name.clone()),
+            Edible(kind),
+            RoundComponent,
+            Position(pos),
+            SpriteBundle {
+                sprite: Sprite {
+                    custom_size: def.sprite_size.map(Vec2::splat),
                     ..Default::default()
                 },
-            ))
-            .add_rollback();
-    }
+                transform: Transform::from_translation(pos.extend(1.0)),
+                texture: def.texture_handle.clone(),
+                ..Default::default()
+            },
+        ))
+        .add_rollback();
 }
 
-pub fn spawn_strawberry_on_player_spawn_points(
+/// Spawns every edible in the registry whose timer finished, in registry order
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_edibles_over_time(
     mut commands: Commands,
-    asset_server: Res<TextureAssets>,
-    player_spawns: Query<&mut PlayerSpawn>,
+    mut agreed_seed: ResMut<AgreedRandom>,
+    registry: Res<EdibleRegistry>,
     timer: Res<EdibleSpawnTimer>,
-    player_query: Query<(Entity, &Transform), With<Player>>,
-    edible_query: Query<(Entity, &Edible, &Transform), (With<Edible>, Without<Expired>)>,
+    spawner_query: Query<(Entity, &Position), With<EncounterSpawner>>,
+    player_spawns: Query<(Entity, &PlayerSpawn)>,
+    player_query: Query<(Entity, &Position), With<Player>>,
+    edible_query: Query<(Entity, &Edible, &Position), Without<Expired>>,
 ) {
-    if !timer.strawberry_timer.finished() {
-        return;
-    }
+    let mut spawn_area = spawner_query.iter().collect::<Vec<_>>();
+    spawn_area.sort_by_key(|e| e.0);
 
-    // find all strawberries on the map and sort them into a vec
-    let mut strawberries = edible_query
-        .iter()
-        .filter(|x| match x.1 {
-            Edible::Strawberry => true,
-            _ => false,
-        })
-        .collect::<Vec<_>>();
-    strawberries.sort_by_key(|e| e.0);
+    let mut spawn_points = player_spawns.iter().collect::<Vec<_>>();
+    spawn_points.sort_by_key(|e| e.0);
 
-    // collect and sort all player locations
     let mut players = player_query.iter().collect::<Vec<_>>();
     players.sort_by_key(|e| e.0);
 
-    // select a player spawn point, check if there is already a strawberry or player there
-    // if not, spawn a strawberry
-    for ps in player_spawns.iter() {
-        let mut free_space = true;
-        let spawn_pos = ps.pos;
-        for s in strawberries.iter() {
-            if s.2.translation.distance(spawn_pos) < TILE_SIZE {
-                free_space = false;
-                break;
-            }
-        }
-        if !free_space {
+    for (kind, def) in registry.edibles.iter().enumerate() {
+        if !timer.finished(kind) {
             continue;
         }
-        for p in players.iter() {
-            if p.1.translation.distance(spawn_pos) < TILE_SIZE {
-                free_space = false;
-                break;
+
+        // positions of this edible already on the map, including the ones spawned just now
+        let mut on_map = edible_query
+            .iter()
+            .filter(|e| e.1 .0 == kind)
+            .map(|e| (e.0, e.2 .0))
+            .collect::<Vec<_>>();
+        on_map.sort_by_key(|e| e.0);
+        let mut on_map = on_map.into_iter().map(|e| e.1).collect::<Vec<_>>();
+
+        for zone in def.spawn_zones.iter() {
+            match zone {
+                SpawnZone::Encounter => {
+                    if spawn_area.is_empty() || on_map.len() >= def.max_on_map {
+                        continue;
+                    }
+                    let idx = agreed_seed.rng.gen_range(0..spawn_area.len());
+                    let pos = spawn_area[idx].1 .0;
+                    spawn_edible(&mut commands, kind, def, pos);
+                    on_map.push(pos);
+                }
+                SpawnZone::PlayerSpawns => {
+                    // skip spawn points already holding this edible or a player
+                    for (_, spawn) in spawn_points.iter() {
+                        if on_map.len() >= def.max_on_map {
+                            break;
+                        }
+                        let pos = spawn.pos;
+                        let blocked = on_map.iter().any(|e| e.distance(pos) < TILE)
+                            || players.iter().any(|p| p.1 .0.distance(pos) < TILE);
+                        if !blocked {
+                            spawn_edible(&mut commands, kind, def, pos);
+                            on_map.push(pos);
+                        }
+                    }
+                }
             }
         }
-        if free_space {
-            // spawn a strawberry
-            commands
-                .spawn((
-                    Name::new("Strawberry"),
-                    Edible::Strawberry,
-                    RoundComponent,
-                    SpriteBundle {
-                        transform: Transform::from_xyz(spawn_pos.x, spawn_pos.y, 1.0),
-                        texture: asset_server.texture_strawberry.clone(),
-                        ..Default::default()
-                    },
-                ))
-                .add_rollback();
-        }
     }
 }
 
-// TODO: add sound
-pub fn player_ate_strawberry_system(
+/// The first player in reach eats the edible and gets all of its effects
+#[allow(clippy::too_many_arguments)]
+pub fn player_ate_edible_system(
     mut commands: Commands,
     frame: Res<FrameCount>,
     sounds: Res<AudioAssets>,
-    mut player_query: Query<(&Transform, &mut PlayerSpeedBoost), With<Player>>,
-    edible_query: Query<(Entity, &Edible, &Transform), Without<Expired>>,
-) {
-    let mut strawberries = edible_query
-        .iter()
-        .filter(|x| match x.1 {
-            Edible::Strawberry => true,
-            _ => false,
-        })
-        .collect::<Vec<_>>();
-    strawberries.sort_by_key(|e| e.0);
-
-    for (pt, mut p) in player_query.iter_mut() {
-        for (s, _, st) in &strawberries {
-            let distance = pt.translation.distance(st.translation);
-
-            if distance < TILE_SIZE / 2.0 + STRAWBERRY_SIZE / 2.0 {
-                p.0 = (p.0 + STRAWBERRY_AMMO_COUNT).clamp(0, PLAYER_SPEED_BOOST_MAX);
-                commands.entity(*s).insert(Expired);
-
-                // spawn desired audio clip
-                commands
-                    .spawn(RollbackSoundBundle {
-                        sound: RollbackSound {
-                            clip: sounds.pickup.clone(),
-                            start_frame: frame.0,
-                            sub_key: s.index(),
-                        },
-                    })
-                    .add_rollback();
-            }
-        }
-    }
-}
-
-pub fn spawn_chili_pepper_over_time(
-    mut commands: Commands,
-    mut agreed_seed: ResMut<AgreedRandom>,
-    asset_server: Res<TextureAssets>,
-    timer: Res<EdibleSpawnTimer>,
-    spawner_query: Query<&Transform, With<EncounterSpawner>>,
+    registry: Res<EdibleRegistry>,
+    mut stats: ResMut<RoundStats>,
+    mut player_query: Query<
+        (
+            Entity,
+            &Position,
+            &Player,
+            &mut FireballAmmo,
+            &mut PoopAmmo,
+            &mut PlayerSpeedBoost,
+            &mut PlayerHealth,
+            &mut StatusEffects,
+        ),
+        Without<Projectile>,
+    >,
+    edible_query: Query<(Entity, &Edible, &Position), Without<Expired>>,
 ) {
-    if timer.chili_pepper_timer.finished() {
-        let spawn_area: Vec<&Transform> = spawner_query.iter().collect();
+    let mut edibles = edible_query.iter().collect::<Vec<_>>();
+    edibles.sort_by_key(|e| e.0);
 
-        let idx = agreed_seed.rng.gen_range(0..spawn_area.len());
-        let pos = spawn_area[idx];
+    let mut players = player_query.iter_mut().collect::<Vec<_>>();
+    players.sort_by_key(|e| e.0);
 
-        commands
-            .spawn((
-                Name::new("ChiliPepper"),
-                Edible::ChiliPepper,
-                RoundComponent,
-                SpriteBundle {
-                    sprite: Sprite {
-                        custom_size: Some(Vec2::splat(CHILI_PEPPER_SIZE * 1.5)),
-                        ..Default::default()
-                    },
-                    transform: Transform::from_xyz(pos.translation.x, pos.translation.y, 1.0),
-                    texture: asset_server.texture_chili_pepper.clone(),
-                    ..Default::default()
-                },
-            ))
-            .add_rollback();
-    }
-}
+    for (e, edible, edible_pos) in edibles {
+        let def = match registry.get(edible.0) {
+            Some(def) => def,
+            None => continue,
+        };
+        let reach = Fixed::from_f32(TILE_SIZE / 2.0 + def.size / 2.0);
+        let eater = players
+            .iter_mut()
+            .find(|p| p.1 .0.distance(edible_pos.0) < reach);
+        let (player_entity, _, player, ammo, poop_ammo, boost, health, effects) = match eater {
+            Some(eater) => eater,
+            None => continue,
+        };
 
-// TODO: add sound
-pub fn player_ate_chili_pepper_system(
-    mut commands: Commands,
-    frame: Res<FrameCount>,
-    sounds: Res<AudioAssets>,
-    mut player_query: Query<(&Transform, &mut FireballAmmo), (With<Player>, Without<Fireball>)>,
-    edible_query: Query<(Entity, &Edible, &Transform), Without<Expired>>,
-) {
-    let mut peppers = edible_query
-        .iter()
-        .filter(|x| match x.1 {
-            Edible::ChiliPepper => true,
-            _ => false,
-        })
-        .collect::<Vec<_>>();
-    peppers.sort_by_key(|e| e.0);
-
-    for (pt, mut ammo) in player_query.iter_mut() {
-        for (s, _, st) in &peppers {
-            let distance = pt.translation.distance(st.translation);
-
-            if distance < TILE_SIZE / 2.0 + CHILI_PEPPER_SIZE / 2.0 {
-                ammo.0 += CHILI_PEPPER_AMMO_COUNT;
-                commands.entity(*s).insert(Expired);
-
-                // spawn desired audio clip
-                commands
-                    .spawn(RollbackSoundBundle {
-                        sound: RollbackSound {
-                            clip: sounds.pickup.clone(),
-                            start_frame: frame.0,
-                            sub_key: s.index(),
-                        },
-                    })
-                    .add_rollback();
+        for effect in def.effects.iter() {
+            match *effect {
+                EdibleEffect::Ammo(amount) => ammo.0 += amount,
+                EdibleEffect::PoopAmmo(amount) => poop_ammo.0 += amount,
+                EdibleEffect::SpeedBoost(amount) => {
+                    boost.0 = (boost.0 + amount).clamp(0, PLAYER_SPEED_BOOST_MAX);
+                }
+                EdibleEffect::Heal(amount) => {
+                    // clamp health gain to max health
+                    if health.0 < PLAYER_HEALTH_MAX {
+                        health.0 = (health.0 + amount).clamp(0, PLAYER_HEALTH_MAX);
+                    }
+                }
+                EdibleEffect::Invulnerable(secs) => {
+                    commands.entity(*player_entity).insert(PlayerInvulnerable {
+                        lifetime: Timer::from_seconds(secs, TimerMode::Once),
+                    });
+                }
+                EdibleEffect::Status(kind, secs) => effects.apply(kind, secs, 1, None),
+                EdibleEffect::Shield(hits, secs) => {
+                    effects.apply(StatusKind::Shield, secs, hits, None)
+                }
             }
         }
-    }
-}
-
-pub fn spawn_lettuce_over_time(
-    mut commands: Commands,
-    mut agreed_seed: ResMut<AgreedRandom>,
-    asset_server: Res<TextureAssets>,
-    timer: Res<EdibleSpawnTimer>,
-    spawner_query: Query<&Transform, With<EncounterSpawner>>,
-) {
-    if timer.lettuce_timer.finished() {
-        let spawn_area: Vec<&Transform> = spawner_query.iter().collect();
-
-        let idx = agreed_seed.rng.gen_range(0..spawn_area.len());
-        let pos = spawn_area[idx];
+        stats.record_edible(player.handle, &def.name);
+        commands.entity(e).insert(Expired);
 
+        // spawn desired audio clip
         commands
-            .spawn((
-                Name::new("Lettuce"),
-                Edible::Lettuce,
-                RoundComponent,
-                SpriteBundle {
-                    transform: Transform::from_xyz(pos.translation.x, pos.translation.y, 1.0),
-                    texture: asset_server.texture_lettuce.clone(),
-                    ..Default::default()
+            .spawn(RollbackSoundBundle {
+                sound: RollbackSound {
+                    clip: sounds.pickup.clone(),
+                    start_frame: frame.0,
+                    sub_key: e.index(),
                 },
-            ))
+            })
             .add_rollback();
     }
 }
 
-pub fn player_ate_lettuce_system(
-    mut commands: Commands,
-    frame: Res<FrameCount>,
-    sounds: Res<AudioAssets>,
-    mut player_query: Query<(&Transform, &mut PlayerHealth), (With<Player>, Without<Fireball>)>,
-    edible_query: Query<(Entity, &Edible, &Transform), Without<Expired>>,
-) {
-    let mut lettuce = edible_query
-        .iter()
-        .filter(|x| match x.1 {
-            Edible::Lettuce => true,
-            _ => false,
-        })
-        .collect::<Vec<_>>();
-    lettuce.sort_by_key(|e| e.0);
-
-    for (pt, mut health) in player_query.iter_mut() {
-        for (s, _, st) in &lettuce {
-            let distance = pt.translation.distance(st.translation);
-
-            if distance < TILE_SIZE / 2.0 + LETTUCE_SIZE / 2.0 {
-                if health.0 < PLAYER_HEALTH_MAX {
-                    // clamp health game to max health
-                    health.0 = (health.0 + LETTUCE_HEALTH_GAIN).clamp(0, PLAYER_HEALTH_MAX);
-                }
-                commands.entity(*s).insert(Expired);
-
-                // spawn desired audio clip
-                commands
-                    .spawn(RollbackSoundBundle {
-                        sound: RollbackSound {
-                            clip: sounds.pickup.clone(),
-                            start_frame: frame.0,
-                            sub_key: s.index(),
-                        },
-                    })
-                    .add_rollback();
-            }
-        }
-    }
-}
-
 // reload_fireball prevents the player from continuously shooting fireballs by holding INPUT_FIRE
 pub fn reload_fireballs(
     mut query: Query<(Entity, &mut FireballReady, &FireballAmmo, &PlayerControls)>,
@@ -839,14 +1237,15 @@ pub fn shoot_fireballs(
     images: Res<TextureAssets>,
     sounds: Res<AudioAssets>,
     frame: Res<FrameCount>,
+    mut stats: ResMut<RoundStats>,
 
     mut query: Query<(
         Entity,
-        &Transform,
+        &Position,
         &mut FireballAmmo,
         &mut FireballReady,
         &PlayerControls,
-        &PlayerSpeed,
+        &StatusEffects,
         &Player,
     )>,
 ) {
@@ -855,16 +1254,16 @@ pub fn shoot_fireballs(
     players.sort_by_key(
         |t: &(
             Entity,
-            &Transform,
+            &Position,
             Mut<FireballAmmo>,
             Mut<FireballReady>,
             &PlayerControls,
-            &PlayerSpeed,
+            &StatusEffects,
             &Player,
         )| t.0,
     );
 
-    for (_, transform, mut ammo, mut ready, controls, speed, player) in players {
+    for (_, position, mut ammo, mut ready, controls, effects, player) in players {
         if !player.active {
             continue; // prevent dead players from shooting
         }
@@ -876,44 +1275,52 @@ pub fn shoot_fireballs(
             }
 
             // position fireball slightly away from players position
-            let player_pos = transform.translation;
-            let pos = player_pos
-                + (Vec3::new(controls.dir.x, controls.dir.y, 0.)) * (TILE_SIZE * 1.5)
-                + FIREBALL_RADIUS;
+            let pos = position.0
+                + controls.dir * Fixed::from_f32(TILE_SIZE * 1.5)
+                + FixedVec2::splat(ProjectileKind::Fireball.def().radius);
 
             debug!(
                 "Spawning fireball by {:?} ammo {:?}, ready {:?}",
                 player, ammo.0, ready.0
             );
 
-            let fireball_id = commands
-                .spawn((
-                    Name::new("Fireball"),
-                    Fireball {
-                        shot_by: player.handle,
-                    },
-                    FireballMovement {
-                        speed: speed.0 as f32,
-                        dir: controls.last_dir,
-                    },
-                    FireballTimer::default(),
-                    RoundComponent,
-                    SpriteBundle {
-                        transform: Transform::from_xyz(pos.x, pos.y, 1.)
-                            .with_rotation(Quat::from_rotation_arc_2d(Vec2::X, controls.last_dir)),
-                        texture: images.texture_fireball.clone(),
-                        ..default()
-                    },
-                ))
-                .add_rollback()
-                .id();
+            // a triple shot fans out around the aimed direction for the price of one fireball
+            let dirs = if effects.has(StatusKind::TripleShot) {
+                vec![
+                    controls.last_dir,
+                    controls
+                        .last_dir
+                        .rotate(FIREBALL_SPREAD_COS, FIREBALL_SPREAD_SIN),
+                    controls
+                        .last_dir
+                        .rotate(FIREBALL_SPREAD_COS, -FIREBALL_SPREAD_SIN),
+                ]
+            } else {
+                vec![controls.last_dir]
+            };
+            let mut fireball_ids = Vec::with_capacity(dirs.len());
+            for dir in dirs {
+                let mut fireball = spawn_projectile(
+                    &mut commands,
+                    &images,
+                    ProjectileKind::Fireball,
+                    player.handle,
+                    pos,
+                    dir,
+                );
+                if effects.has(StatusKind::Homing) {
+                    fireball.insert(ProjectileHoming);
+                }
+                fireball_ids.push(fireball.id());
+                stats.record_fireball_fired(player.handle);
+            }
 
             ammo.0 -= 1;
             ready.0 = false;
 
             debug!(
-                "Spawned fireball {:?} by {:?} ammo {:?}, ready {:?}",
-                fireball_id, player, ammo.0, ready.0
+                "Spawned fireballs {:?} by {:?} ammo {:?}, ready {:?}",
+                fireball_ids, player, ammo.0, ready.0
             );
 
             // spawn desired audio clip
@@ -922,7 +1329,7 @@ pub fn shoot_fireballs(
                     sound: RollbackSound {
                         clip: sounds.fireball_shot.clone(),
                         start_frame: frame.0,
-                        sub_key: fireball_id.index(),
+                        sub_key: fireball_ids[0].index(),
                     },
                 })
                 .add_rollback();
@@ -930,142 +1337,479 @@ pub fn shoot_fireballs(
     }
 }
 
-pub fn move_fireballs(
-    mut query: Query<(Entity, &mut Transform, &FireballMovement), (With<Fireball>, With<Rollback>)>,
+/// Shell-bash hits every other player in reach, pushing them away from the basher
+pub fn shell_bash(
+    mut commands: Commands,
+    frame: Res<RollbackFrameCount>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut goose_hits: EventWriter<GooseHitEvent>,
+    goose_query: Query<(Entity, &Position), (With<Goose>, Without<Expired>)>,
+    mut query: Query<
+        (
+            Entity,
+            &Position,
+            &Player,
+            &PlayerControls,
+            &mut PlayerVelocity,
+            Option<&BashCooldown>,
+            Option<&PlayerInvulnerable>,
+        ),
+        With<Rollback>,
+    >,
 ) {
-    // collect and sort all fireballs in play so we move them in a deterministic order
-    let mut fireballs = query.iter_mut().collect::<Vec<_>>();
-    fireballs.sort_by_key(|t| t.0);
+    let mut players = query
+        .iter()
+        .map(|(e, t, p, c, _, cooldown, invulnerable)| {
+            (
+                e,
+                t.0,
+                *p,
+                c.clone(),
+                cooldown.is_some(),
+                invulnerable.is_some(),
+            )
+        })
+        .collect::<Vec<_>>();
+    players.sort_by_key(|p| p.0);
 
-    for (_, mut transform, movement) in fireballs {
-        transform.translation += (movement.dir * (movement.speed * 0.05)).extend(0.);
+    let mut geese = goose_query.iter().collect::<Vec<_>>();
+    geese.sort_by_key(|g| g.0);
+
+    let mut impulses = Vec::new();
+    for (entity, pos, player, controls, cooling_down, _) in players.iter() {
+        if !player.active || !controls.bashing || *cooling_down {
+            continue;
+        }
+        commands.entity(*entity).insert(BashCooldown::default());
+
+        for (victim_entity, victim_pos, victim, _, _, invulnerable) in players.iter() {
+            if victim.handle == player.handle || !victim.active || *invulnerable {
+                continue;
+            }
+            if pos.distance(*victim_pos) > BASH_RANGE {
+                continue;
+            }
+
+            damage_events.send(DamageEvent {
+                attacker: player.handle,
+                victim: victim.handle,
+                source: DamageSource::Bash,
+                amount: BASH_DAMAGE,
+                frame: (*frame).into(),
+            });
+            // players standing on top of each other are pushed the way the basher faces
+            let dir = match (*victim_pos - *pos).normalize_or_zero() {
+                FixedVec2::ZERO => controls.last_dir,
+                dir => dir,
+            };
+            impulses.push((*victim_entity, dir * BASH_KNOCKBACK));
+        }
+
+        // geese get hurt too but are too heavy to push around
+        for (goose, goose_pos) in geese.iter() {
+            if pos.distance(goose_pos.0) <= BASH_RANGE {
+                goose_hits.send(GooseHitEvent {
+                    goose: *goose,
+                    attacker: player.handle,
+                    amount: BASH_DAMAGE,
+                });
+            }
+        }
+    }
+
+    for (entity, impulse) in impulses {
+        if let Ok((_, _, _, _, mut velocity, _, _)) = query.get_mut(entity) {
+            velocity.0 += impulse;
+        }
     }
 }
 
-pub fn tick_fireball_timers(mut query: Query<(Entity, &mut FireballTimer), Without<Expired>>) {
-    // collect and sort all timers in play so we tick them in a deterministic order
-    let mut timers = query.iter_mut().collect::<Vec<_>>();
-    timers.sort_by_key(|t| t.0);
+pub fn tick_bash_cooldowns(mut commands: Commands, mut query: Query<(Entity, &mut BashCooldown)>) {
+    let mut players = query.iter_mut().collect::<Vec<_>>();
+    players.sort_by_key(|e| e.0);
 
-    for (_, mut timer) in timers {
-        timer.lifetime.tick(Duration::from_millis(FIXED_TICK_MS));
+    for (entity, mut cooldown) in players {
+        cooldown.timer.tick(Duration::from_millis(FIXED_TICK_MS));
+        if cooldown.timer.finished() {
+            commands.entity(entity).remove::<BashCooldown>();
+        }
     }
 }
 
-pub fn despawn_old_fireballs(
-    mut commands: Commands,
-    mut query: Query<(Entity, &FireballTimer), Without<Expired>>,
+// apply_damage subtracts health for every damage event sent this frame and records the last hit,
+// shielded players lose a shield stack instead
+pub fn apply_damage(
+    mut stats: ResMut<RoundStats>,
+    mut damage_events: EventReader<DamageEvent>,
+    mut player_query: Query<
+        (
+            &Player,
+            &mut PlayerHealth,
+            &mut LastHitBy,
+            &mut StatusEffects,
+        ),
+        With<Rollback>,
+    >,
 ) {
-    trace!("despawn_old_fireballs");
+    for event in damage_events.read() {
+        for (player, mut health, mut last_hit, mut effects) in player_query.iter_mut() {
+            if player.handle != event.victim {
+                continue;
+            }
+            if event.source.is_hit() && effects.absorb_hit() {
+                debug!("Player {:?} shield absorbed a hit", event.victim);
+                continue;
+            }
 
-    // collect and sort all fireballs in play so we despawn them in a deterministic order
-    let mut fireballs = query.iter_mut().collect::<Vec<_>>();
-    fireballs.sort_by_key(|e| e.0);
+            stats.record_damage(event);
 
-    for (fireball, timer) in fireballs {
-        if timer.lifetime.finished() {
-            debug!("Despawning old fireball {:?}", fireball);
-            commands.entity(fireball).insert(Expired);
+            health.0 -= event.amount;
+            *last_hit = LastHitBy {
+                attacker: Some(event.attacker),
+                source: event.source,
+                frame: event.frame,
+            };
+            debug!(
+                "Player {:?} hit by {:?}, new health {:?}",
+                event.victim, event.attacker, health.0
+            );
         }
     }
 }
 
-pub fn fireball_damage_players(
+pub fn kill_players(
     mut commands: Commands,
+    frame: Res<RollbackFrameCount>,
+    mut score: ResMut<RoundScore>,
+    mut stats: ResMut<RoundStats>,
+    settings: Res<MatchSettings>,
     mut player_query: Query<
-        (Entity, &mut PlayerHealth, &Transform, &Player),
-        (With<Rollback>, Without<Fireball>),
+        (
+            Entity,
+            &mut Player,
+            &PlayerHealth,
+            &LastHitBy,
+            &mut PlayerLives,
+            &mut FrameAnimation,
+            &mut TextureAtlasSprite,
+        ),
+        (With<Player>, Without<Projectile>),
     >,
-    fireball_query: Query<(Entity, &Transform, &Fireball), With<Rollback>>,
 ) {
-    // collect and sort all players and fireballs in play so we damage players in a deterministic order
+    // collect and sort all players in play so we kill players in a deterministic order
     let mut players = player_query.iter_mut().collect::<Vec<_>>();
     players.sort_by_key(|e| e.0);
 
-    let mut fireballs = fireball_query.iter().collect::<Vec<_>>();
-    fireballs.sort_by_key(|e| e.0);
+    for (entity, mut player, health, last_hit, mut lives, mut animation, mut sprite) in players {
+        if !player.active || health.0 > 0 {
+            continue;
+        }
 
-    for (_, mut health, transform, player) in players {
-        for (entity, fireball_transform, fireball) in fireballs.clone() {
-            if !player.active {
-                continue; // don't continue to damage dead players
-            }
-            if fireball.shot_by == player.handle {
-                continue; // don't allow player to suicide
-            };
+        animation.timer.set_mode(TimerMode::Once);
+        sprite.flip_y = true;
+        player.active = false;
+        if settings.mode == GameMode::Elimination {
+            lives.0 -= 1;
+        }
+        score.record_death(player.handle);
 
-            let distance = transform
-                .translation
-                .distance(fireball_transform.translation);
-
-            if distance < TILE_SIZE + FIREBALL_RADIUS {
-                health.0 -= FIREBALL_DAMAGE;
-                commands.entity(entity).insert(Expired); // despawn fireball
-                debug!(
-                    "Fireball {:?} hit player, new health {:?}",
-                    entity, health.0
-                )
-            }
+        // credit the kill to whoever landed the last hit
+        let attacker = last_hit.attacker.filter(|a| *a != player.handle);
+        if let Some(attacker) = attacker {
+            score.credit_kill(attacker);
+        }
+
+        commands
+            .spawn((
+                Name::new("KillFeedEntry"),
+                KillFeedEntry {
+                    attacker,
+                    victim: player.handle,
+                    source: last_hit.source,
+                    frame: (*frame).into(),
+                },
+                RoundComponent,
+            ))
+            .add_rollback();
+
+        if lives.0 > 0 {
+            commands
+                .entity(entity)
+                .insert(PlayerRespawnTimer::default());
+        } else {
+            stats.record_elimination(player.handle, (*frame).into());
         }
     }
 }
 
-pub fn kill_players(
+/// GGRS reports the same disconnect frame to every remaining peer, so dropping
+/// the player on that frame keeps all simulations in agreement.
+pub fn eliminate_disconnected_players(
+    mut commands: Commands,
+    frame: Res<RollbackFrameCount>,
+    inputs: Res<PlayerInputs<GGRSConfig>>,
+    mut stats: ResMut<RoundStats>,
     mut player_query: Query<
         (
             Entity,
             &mut Player,
-            &PlayerHealth,
+            &mut PlayerLives,
             &mut FrameAnimation,
             &mut TextureAtlasSprite,
         ),
-        (With<Player>, Without<Fireball>),
+        Without<Projectile>,
     >,
 ) {
-    // collect and sort all players in play so we kill players in a deterministic order
     let mut players = player_query.iter_mut().collect::<Vec<_>>();
     players.sort_by_key(|e| e.0);
 
-    for (_, mut player, health, mut animation, mut sprite) in players {
-        if health.0 <= 0 {
-            animation.timer.set_mode(TimerMode::Once);
-            sprite.flip_y = true;
-            player.active = false;
+    for (entity, mut player, mut lives, mut animation, mut sprite) in players {
+        if inputs[player.handle].1 != InputStatus::Disconnected {
+            continue;
+        }
+        if !player.active && lives.0 <= 0 {
+            continue; // already out of the round
+        }
+
+        animation.timer.set_mode(TimerMode::Once);
+        sprite.flip_y = true;
+        player.active = false;
+        lives.0 = 0;
+        stats.record_elimination(player.handle, (*frame).into());
+
+        commands
+            .entity(entity)
+            .remove::<PlayerRespawnTimer>()
+            .remove::<PlayerInvulnerable>();
+        commands
+            .spawn((
+                Name::new("KillFeedEntry"),
+                KillFeedEntry {
+                    attacker: None,
+                    victim: player.handle,
+                    source: DamageSource::Disconnect,
+                    frame: (*frame).into(),
+                },
+                RoundComponent,
+            ))
+            .add_rollback();
+    }
+}
+
+pub fn respawn_players(
+    mut commands: Commands,
+    spawn_query: Query<(Entity, &PlayerSpawn)>,
+    mut player_query: Query<
+        (
+            Entity,
+            &mut Player,
+            &mut PlayerHealth,
+            &mut LastHitBy,
+            &mut PlayerRespawnTimer,
+            &mut Position,
+            &mut FrameAnimation,
+            &mut TextureAtlasSprite,
+        ),
+        Without<Projectile>,
+    >,
+    other_players: Query<(&Player, &Position), Without<PlayerRespawnTimer>>,
+) {
+    // collect and sort all spawn points so every peer picks the same one
+    let mut spawns = spawn_query.iter().collect::<Vec<_>>();
+    spawns.sort_by_key(|e| e.0);
+    let spawns = spawns.iter().map(|s| s.1.pos).collect::<Vec<_>>();
+
+    let mut occupied = other_players
+        .iter()
+        .filter(|(p, _)| p.active)
+        .map(|(_, p)| p.0)
+        .collect::<Vec<_>>();
+
+    let mut players = player_query.iter_mut().collect::<Vec<_>>();
+    players.sort_by_key(|e| e.0);
+
+    for (
+        entity,
+        mut player,
+        mut health,
+        mut last_hit,
+        mut timer,
+        mut position,
+        mut animation,
+        mut sprite,
+    ) in players
+    {
+        timer.delay.tick(Duration::from_millis(FIXED_TICK_MS));
+        if !timer.delay.finished() {
+            continue;
+        }
+
+        if let Some(pos) = furthest_free_spawn(&spawns, &occupied) {
+            position.0 = pos;
+        }
+        occupied.push(position.0);
+
+        animation.timer.set_mode(TimerMode::Repeating);
+        sprite.flip_y = false;
+        health.0 = PLAYER_HEALTH_MAX;
+        *last_hit = LastHitBy::default();
+        player.active = true;
+
+        commands
+            .entity(entity)
+            .remove::<PlayerRespawnTimer>()
+            .insert(PlayerInvulnerable::default());
+    }
+}
+
+/// Picks the spawn point with the most distance to its closest occupied position,
+/// skipping any spawn point a player is already standing on.
+fn furthest_free_spawn(spawns: &[FixedVec2], occupied: &[FixedVec2]) -> Option<FixedVec2> {
+    let mut best: Option<(FixedVec2, Fixed)> = None;
+
+    for spawn in spawns {
+        let closest = occupied
+            .iter()
+            .map(|o| spawn.distance(*o))
+            .fold(Fixed::MAX, Fixed::min);
+
+        if closest < TILE {
+            continue; // spawn point is taken
+        }
+
+        match best {
+            Some((_, distance)) if distance >= closest => (),
+            _ => best = Some((*spawn, closest)),
+        }
+    }
+
+    best.map(|(pos, _)| pos)
+}
+
+pub fn tick_invulnerability(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut PlayerInvulnerable)>,
+) {
+    let mut players = query.iter_mut().collect::<Vec<_>>();
+    players.sort_by_key(|e| e.0);
+
+    for (entity, mut invulnerable) in players {
+        invulnerable
+            .lifetime
+            .tick(Duration::from_millis(FIXED_TICK_MS));
+        if invulnerable.lifetime.finished() {
+            commands.entity(entity).remove::<PlayerInvulnerable>();
         }
     }
 }
 
+pub fn despawn_old_kill_feed_entries(
+    mut commands: Commands,
+    frame: Res<RollbackFrameCount>,
+    query: Query<(Entity, &KillFeedEntry), Without<Expired>>,
+) {
+    let frame = i32::from(*frame);
+
+    let mut entries = query.iter().collect::<Vec<_>>();
+    entries.sort_by_key(|e| e.0);
+
+    for (entity, entry) in entries {
+        if frame - entry.frame >= KILL_FEED_LIFETIME {
+            commands.entity(entity).insert(Expired);
+        }
+    }
+}
+
+pub fn tick_round_timer(mut round_timer: ResMut<RoundTimer>) {
+    round_timer.0.tick(Duration::from_millis(FIXED_TICK_MS));
+}
+
+// tint_player_sprites blinks recently respawned players, fades invisible ones and tints players
+// by their strongest status effect, this is visual only
+pub fn tint_player_sprites(
+    mut query: Query<
+        (
+            &mut TextureAtlasSprite,
+            &StatusEffects,
+            Option<&PlayerInvulnerable>,
+        ),
+        With<Player>,
+    >,
+) {
+    for (mut sprite, effects, invulnerable) in query.iter_mut() {
+        let alpha = match invulnerable {
+            Some(i) if (i.lifetime.elapsed_secs() * 10.) as i32 % 2 == 0 => 0.3,
+            _ if effects.has(StatusKind::Invisibility) => 0.4,
+            _ => 1.0,
+        };
+        let tint = [StatusKind::Burning, StatusKind::Poison, StatusKind::Shield]
+            .into_iter()
+            .find(|kind| effects.has(*kind))
+            .map(|kind| kind.color())
+            .unwrap_or(Color::WHITE);
+        sprite.color = tint.with_a(alpha);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn check_win_state(
     mut commands: Commands,
     mut app_state: ResMut<NextState<AppState>>,
     mut game_state: ResMut<NextState<GameState>>,
     player_handle: Option<Res<LocalHandle>>,
-    player_query: Query<(Entity, &Player), Without<Fireball>>,
+    spectator: Option<Res<Spectator>>,
+    score: Option<Res<RoundScore>>,
+    stats: Option<Res<RoundStats>>,
+    round_timer: Option<Res<RoundTimer>>,
+    names: Res<PlayerNames>,
+    player_query: Query<(Entity, &Player, &PlayerLives), Without<Projectile>>,
 ) {
     let local_handle = match player_handle {
         Some(handle) => handle.0,
         None => return, // Session hasn't started yet
     };
+    let (score, stats, round_timer) = match (score, stats, round_timer) {
+        (Some(score), Some(stats), Some(timer)) => (score, stats, timer),
+        _ => return, // Round hasn't started yet
+    };
 
     let mut players = player_query.iter().collect::<Vec<_>>();
     players.sort_by_key(|e| e.0);
 
+    // players waiting to respawn are still in the round
     let mut remaning_active = vec![];
-    for (_, player) in players {
-        if player.active {
+    for (_, player, lives) in players {
+        if player.active || lives.0 > 0 {
             remaning_active.push(player);
         }
     }
-    if remaning_active.len() == 1 {
-        if remaning_active[0].handle == local_handle {
-            commands.insert_resource(MatchData {
-                result: format!("You Win!"),
-            })
+
+    let winner = if remaning_active.len() == 1 {
+        Some(remaning_active[0].handle)
+    } else if remaning_active.is_empty() || round_timer.0.finished() {
+        // time is up or everyone went down at once, highest score wins
+        score.leader()
+    } else {
+        None
+    };
+
+    if let Some(winner) = winner {
+        let mut stats = stats.clone();
+        stats.finalize(winner, &score);
+
+        let result = if spectator.is_some() {
+            format!("{} Wins!", names.get(winner))
+        } else if winner == local_handle {
+            "You Win!".to_owned()
         } else {
-            commands.insert_resource(MatchData {
-                result: format!("You Lost!"),
-            })
-        }
+            "You Lost!".to_owned()
+        };
+        commands.insert_resource(MatchResults::from_stats(&stats, &names, winner));
+        commands.insert_resource(MatchData {
+            result,
+            local_handle,
+            stats,
+        });
         app_state.set(AppState::Win);
         game_state.set(GameState::Paused);
     }
@@ -1128,7 +1872,7 @@ pub fn update_health_bars(
         let half = PLAYER_HEALTH_MID as f32;
         let x_offset = half - half * health_percent;
 
-        transform.scale = vec3(health_percent as f32, 1.0, 1.0);
+        transform.scale = vec3(health_percent, 1.0, 1.0);
         transform.translation = vec3(-x_offset, HEALTH_BAR_Y_OFFSET, 0.2)
     }
 }
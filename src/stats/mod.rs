@@ -0,0 +1,3 @@
+pub mod plugin;
+pub mod profile;
+pub mod resources;
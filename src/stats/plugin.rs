@@ -0,0 +1,38 @@
+use bevy::prelude::*;
+
+use crate::{menu::win::MatchData, player::resources::Spectator, AppState};
+
+use super::profile::PlayerProfile;
+
+pub struct StatsPlugin;
+
+/// This plugin loads the local player profile and records finished rounds into it
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_profile)
+            .add_systems(OnEnter(AppState::Win), record_match);
+    }
+}
+
+fn load_profile(mut commands: Commands) {
+    commands.insert_resource(PlayerProfile::load());
+}
+
+fn record_match(
+    mut profile: ResMut<PlayerProfile>,
+    match_data: Option<Res<MatchData>>,
+    spectator: Option<Res<Spectator>>,
+) {
+    let match_data = match match_data {
+        Some(data) => data,
+        None => return, // round ended without a result
+    };
+    if spectator.is_some() {
+        return; // we only watched this round
+    }
+
+    if let Some(stats) = match_data.stats.players.get(match_data.local_handle) {
+        profile.record_round(stats);
+        profile.save();
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::resources::PlayerStats;
+use crate::menu::handshake::CompatibilityRange;
+use crate::player::resources::NetcodeSettings;
+
+#[cfg(not(target_arch = "wasm32"))]
+const PROFILE_FILE: &str = "profile.ron";
+#[cfg(target_arch = "wasm32")]
+const PROFILE_STORAGE_KEY: &str = "turtletime_profile";
+
+/// Lifetime stats for the local player, persisted between sessions.
+#[derive(Clone, Debug, Default, Deserialize, Resource, Serialize)]
+#[serde(default)]
+pub struct PlayerProfile {
+    pub name: String,
+    pub rounds_played: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub kills: i32,
+    pub deaths: i32,
+    pub most_kills_in_round: i32,
+    pub fireballs_fired: u32,
+    pub fireballs_hit: u32,
+    pub poop_damage_dealt: i32,
+    pub geese_killed: u32,
+    pub distance_moved: f32,
+    pub edibles_eaten: BTreeMap<String, u32>,
+    /// Netcode preferences from the options menu, used when hosting and in local matches
+    pub netcode: NetcodeSettings,
+    /// Protocol versions accepted from other peers, narrowed to what this build supports
+    pub compatibility: CompatibilityRange,
+}
+
+impl PlayerProfile {
+    pub fn record_round(&mut self, stats: &PlayerStats) {
+        self.rounds_played += 1;
+        if stats.placement == 1 {
+            self.wins += 1;
+        } else {
+            self.losses += 1;
+        }
+        self.kills += stats.kills;
+        self.deaths += stats.deaths;
+        self.most_kills_in_round = self.most_kills_in_round.max(stats.kills);
+        self.fireballs_fired += stats.fireballs_fired;
+        self.fireballs_hit += stats.fireballs_hit;
+        self.poop_damage_dealt += stats.poop_damage_dealt;
+        self.geese_killed += stats.geese_killed;
+        self.distance_moved += stats.distance_moved;
+        for (edible, count) in stats.edibles_eaten.iter() {
+            *self.edibles_eaten.entry(edible.clone()).or_default() += count;
+        }
+    }
+
+    pub fn win_rate(&self) -> f32 {
+        if self.rounds_played == 0 {
+            return 0.;
+        }
+        self.wins as f32 / self.rounds_played as f32 * 100.
+    }
+
+    pub fn accuracy(&self) -> f32 {
+        if self.fireballs_fired == 0 {
+            return 0.;
+        }
+        self.fireballs_hit as f32 / self.fireballs_fired as f32 * 100.
+    }
+
+    pub fn load() -> PlayerProfile {
+        let mut profile = match read_profile() {
+            Some(data) => ron::from_str(&data).unwrap_or_else(|e| {
+                warn!("unable to parse player profile, starting fresh: {:?}", e);
+                PlayerProfile::default()
+            }),
+            None => PlayerProfile::default(),
+        };
+        // the file may have been edited by hand
+        profile.netcode = profile.netcode.clamped();
+        profile.compatibility = profile.compatibility.clamped();
+        profile
+    }
+
+    pub fn save(&self) {
+        match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(data) => write_profile(&data),
+            Err(e) => warn!("unable to serialize player profile: {:?}", e),
+        }
+    }
+}
+
+/// `None` when there is no user data directory, the profile then lives for this session only
+#[cfg(not(target_arch = "wasm32"))]
+fn profile_dir() -> Option<std::path::PathBuf> {
+    let base = std::env::var_os("APPDATA")
+        .or_else(|| std::env::var_os("XDG_DATA_HOME"))
+        .map(std::path::PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|h| std::path::Path::new(&h).join(".local/share"))
+        })?;
+    Some(base.join("turtletime"))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_profile() -> Option<String> {
+    std::fs::read_to_string(profile_dir()?.join(PROFILE_FILE)).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_profile(data: &str) {
+    let Some(dir) = profile_dir() else {
+        warn!("no user data directory (APPDATA, XDG_DATA_HOME or HOME), player profile not saved");
+        return;
+    };
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        warn!("unable to create profile directory {:?}: {:?}", dir, e);
+        return;
+    }
+    if let Err(e) = std::fs::write(dir.join(PROFILE_FILE), data) {
+        warn!("unable to write player profile: {:?}", e);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_profile() -> Option<String> {
+    local_storage()?.get_item(PROFILE_STORAGE_KEY).ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write_profile(data: &str) {
+    match local_storage() {
+        Some(storage) => {
+            if let Err(e) = storage.set_item(PROFILE_STORAGE_KEY, data) {
+                warn!("unable to write player profile: {:?}", e);
+            }
+        }
+        None => warn!("local storage unavailable, player profile not saved"),
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+
+use crate::player::{components::DamageSource, events::DamageEvent, resources::RoundScore};
+
+/// Counters collected for a single player over the course of a round.
+#[derive(Clone, Debug, Default)]
+pub struct PlayerStats {
+    pub handle: usize,
+    pub edibles_eaten: BTreeMap<String, u32>,
+    pub fireballs_fired: u32,
+    pub fireballs_hit: u32,
+    pub poop_damage_dealt: i32,
+    pub geese_killed: u32,
+    pub damage_dealt: i32,
+    pub damage_taken: i32,
+    pub distance_moved: f32,
+    pub kills: i32,
+    pub deaths: i32,
+    pub eliminated_frame: Option<i32>,
+    pub placement: usize,
+}
+
+impl PlayerStats {
+    pub fn edibles_total(&self) -> u32 {
+        self.edibles_eaten.values().sum()
+    }
+}
+
+/// Per-round stats for every player handle, registered for rollback
+/// so counters from mispredicted frames are undone.
+#[derive(Clone, Debug, Default, Resource)]
+pub struct RoundStats {
+    pub players: Vec<PlayerStats>,
+}
+
+impl RoundStats {
+    pub fn new(num_players: usize) -> RoundStats {
+        RoundStats {
+            players: (0..num_players)
+                .map(|handle| PlayerStats {
+                    handle,
+                    ..Default::default()
+                })
+                .collect(),
+        }
+    }
+
+    pub fn record_edible(&mut self, handle: usize, edible: &str) {
+        if let Some(stats) = self.players.get_mut(handle) {
+            *stats.edibles_eaten.entry(edible.to_owned()).or_default() += 1;
+        }
+    }
+
+    pub fn record_fireball_fired(&mut self, handle: usize) {
+        if let Some(stats) = self.players.get_mut(handle) {
+            stats.fireballs_fired += 1;
+        }
+    }
+
+    /// Self inflicted damage, e.g. unattributed poison, only counts as taken
+    pub fn record_damage(&mut self, event: &DamageEvent) {
+        let attacker = if event.attacker == event.victim {
+            None
+        } else {
+            self.players.get_mut(event.attacker)
+        };
+        if let Some(stats) = attacker {
+            stats.damage_dealt += event.amount;
+            match event.source {
+                DamageSource::Fireball => stats.fireballs_hit += 1,
+                DamageSource::Poop => stats.poop_damage_dealt += event.amount,
+                DamageSource::Disconnect
+                | DamageSource::Poison
+                | DamageSource::Burning
+                | DamageSource::Bash => (),
+            }
+        }
+        if let Some(stats) = self.players.get_mut(event.victim) {
+            stats.damage_taken += event.amount;
+        }
+    }
+
+    pub fn record_goose_kill(&mut self, handle: usize) {
+        if let Some(stats) = self.players.get_mut(handle) {
+            stats.geese_killed += 1;
+        }
+    }
+
+    pub fn record_distance(&mut self, handle: usize, distance: f32) {
+        if let Some(stats) = self.players.get_mut(handle) {
+            stats.distance_moved += distance;
+        }
+    }
+
+    pub fn record_elimination(&mut self, handle: usize, frame: i32) {
+        if let Some(stats) = self.players.get_mut(handle) {
+            stats.eliminated_frame = Some(frame);
+        }
+    }
+
+    /// Copies kills and deaths from the round score and ranks every player.
+    /// The winner places first, players still in the round come next,
+    /// then everyone else in reverse elimination order.
+    pub fn finalize(&mut self, winner: usize, score: &RoundScore) {
+        for stats in self.players.iter_mut() {
+            stats.kills = score.kills.get(stats.handle).copied().unwrap_or_default();
+            stats.deaths = score.deaths.get(stats.handle).copied().unwrap_or_default();
+        }
+
+        let mut ranking = self.players.iter().collect::<Vec<_>>();
+        ranking.sort_by(|a, b| {
+            (b.handle == winner)
+                .cmp(&(a.handle == winner))
+                .then(
+                    a.eliminated_frame
+                        .is_some()
+                        .cmp(&b.eliminated_frame.is_some()),
+                )
+                .then(b.eliminated_frame.cmp(&a.eliminated_frame))
+                .then(b.kills.cmp(&a.kills))
+                .then(a.handle.cmp(&b.handle))
+        });
+        let placements = ranking
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s.handle, i + 1))
+            .collect::<Vec<_>>();
+
+        for (handle, placement) in placements {
+            self.players[handle].placement = placement;
+        }
+    }
+}
+
+/// Formats a placement as an ordinal, e.g. 1st, 2nd, 3rd
+pub fn ordinal(placement: usize) -> String {
+    let suffix = match (placement % 10, placement % 100) {
+        (1, p) if p != 11 => "st",
+        (2, p) if p != 12 => "nd",
+        (3, p) if p != 13 => "rd",
+        _ => "th",
+    };
+    format!("{}{}", placement, suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn damage(attacker: usize, victim: usize, source: DamageSource) -> DamageEvent {
+        DamageEvent {
+            attacker,
+            victim,
+            source,
+            amount: 2,
+            frame: 0,
+        }
+    }
+
+    #[test]
+    fn damage_is_credited_to_the_attacker() {
+        let mut stats = RoundStats::new(2);
+        stats.record_damage(&damage(0, 1, DamageSource::Poop));
+        assert_eq!(stats.players[0].damage_dealt, 2);
+        assert_eq!(stats.players[0].poop_damage_dealt, 2);
+        assert_eq!(stats.players[1].damage_taken, 2);
+    }
+
+    #[test]
+    fn self_inflicted_damage_is_never_credited() {
+        let mut stats = RoundStats::new(2);
+        stats.record_damage(&damage(1, 1, DamageSource::Poison));
+        assert_eq!(stats.players[1].damage_dealt, 0);
+        assert_eq!(stats.players[1].damage_taken, 2);
+    }
+}